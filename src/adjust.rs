@@ -0,0 +1,208 @@
+use opencv::{
+  core::{self, Mat, Scalar, Vector, CV_8UC1},
+  prelude::*,
+};
+
+use crate::handler;
+
+/// Software image adjustment (independent of the camera controls)
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Adjustment {
+  Brightness,
+  Contrast,
+  Gamma,
+  Saturation,
+}
+
+/// Value range of an adjustment
+#[derive(Clone, Copy, Debug)]
+pub struct AdjustmentRange {
+  pub min: f64,
+  pub max: f64,
+  pub step: f64,
+  pub default: f64,
+}
+
+impl Adjustment {
+  /// All adjustments in the panel order
+  pub const ALL: [Adjustment; 4] = [
+    Adjustment::Brightness,
+    Adjustment::Contrast,
+    Adjustment::Gamma,
+    Adjustment::Saturation,
+  ];
+
+  /// Returns a label (shown in the adjustments panel)
+  pub fn label(&self) -> &'static str {
+    match self {
+      Adjustment::Brightness => "Brightness",
+      Adjustment::Contrast => "Contrast",
+      Adjustment::Gamma => "Gamma",
+      Adjustment::Saturation => "Saturation",
+    }
+  }
+
+  /// Returns a value range (brightness is an offset, the rest are factors)
+  pub fn range(&self) -> AdjustmentRange {
+    match self {
+      Adjustment::Brightness => AdjustmentRange {
+        min: -128.0,
+        max: 128.0,
+        step: 8.0,
+        default: 0.0,
+      },
+      Adjustment::Contrast => AdjustmentRange {
+        min: 0.0,
+        max: 3.0,
+        step: 0.1,
+        default: 1.0,
+      },
+      Adjustment::Gamma => AdjustmentRange {
+        min: 0.2,
+        max: 3.0,
+        step: 0.1,
+        default: 1.0,
+      },
+      Adjustment::Saturation => AdjustmentRange {
+        min: 0.0,
+        max: 3.0,
+        step: 0.1,
+        default: 1.0,
+      },
+    }
+  }
+}
+
+impl AdjustmentRange {
+  /// Returns a position of the value in the range (0.0 - 1.0)
+  pub fn position(&self, value: f64) -> f64 {
+    ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+  }
+}
+
+/// Brightness, contrast, gamma and saturation of the resized frame.
+///
+/// Brightness, contrast and gamma share a single lookup table,
+/// the saturation blends the frame with its grayscale version.
+pub struct ImageAdjustments {
+  brightness: f64,
+  contrast: f64,
+  gamma: f64,
+  saturation: f64,
+  lut: [u8; 256],
+}
+
+impl Default for ImageAdjustments {
+  fn default() -> Self {
+    let mut image_adjustments = Self {
+      brightness: Adjustment::Brightness.range().default,
+      contrast: Adjustment::Contrast.range().default,
+      gamma: Adjustment::Gamma.range().default,
+      saturation: Adjustment::Saturation.range().default,
+      lut: [0; 256],
+    };
+
+    image_adjustments.update_lut();
+    image_adjustments
+  }
+}
+
+impl ImageAdjustments {
+  /// Returns a current value of the adjustment
+  pub fn value(&self, adjustment: Adjustment) -> f64 {
+    match adjustment {
+      Adjustment::Brightness => self.brightness,
+      Adjustment::Contrast => self.contrast,
+      Adjustment::Gamma => self.gamma,
+      Adjustment::Saturation => self.saturation,
+    }
+  }
+
+  /// Sets the adjustment (clamped into its range)
+  pub fn set(&mut self, adjustment: Adjustment, value: f64) {
+    let range = adjustment.range();
+    // Rounded to whole steps, repeated small steps don't drift
+    let value = ((value / range.step).round() * range.step).clamp(range.min, range.max);
+
+    match adjustment {
+      Adjustment::Brightness => self.brightness = value,
+      Adjustment::Contrast => self.contrast = value,
+      Adjustment::Gamma => self.gamma = value,
+      Adjustment::Saturation => self.saturation = value,
+    }
+
+    self.update_lut();
+  }
+
+  /// Moves the adjustment by a number of steps
+  pub fn step(&mut self, adjustment: Adjustment, steps: f64) {
+    self.set(
+      adjustment,
+      self.value(adjustment) + adjustment.range().step * steps,
+    );
+  }
+
+  /// Resets all adjustments
+  pub fn reset(&mut self) {
+    *self = Self::default();
+  }
+
+  /// Checks if the adjustments leave the frame unchanged
+  pub fn is_neutral(&self) -> bool {
+    Adjustment::ALL
+      .into_iter()
+      .all(|adjustment| self.value(adjustment) == adjustment.range().default)
+  }
+
+  /// Applies the adjustments to a BGR frame
+  pub fn apply(&self, frame: &Mat) -> opencv::Result<Mat> {
+    if self.is_neutral() {
+      return Ok(frame.clone());
+    }
+
+    let mut lut = Mat::new_rows_cols_with_default(1, 256, CV_8UC1, Scalar::all(0.0))?;
+    lut.data_bytes_mut()?.copy_from_slice(&self.lut);
+
+    let mut adjusted_frame = Mat::default();
+    core::lut(frame, &lut, &mut adjusted_frame)?;
+
+    if self.saturation == Adjustment::Saturation.range().default {
+      return Ok(adjusted_frame);
+    }
+
+    let mut gray_frame = Mat::default();
+    handler::convert_into_grayscale(&adjusted_frame, &mut gray_frame)?;
+
+    let mut gray_channels = Vector::<Mat>::new();
+    for _ in 0..3 {
+      gray_channels.push(gray_frame.clone());
+    }
+
+    let mut gray_bgr_frame = Mat::default();
+    core::merge(&gray_channels, &mut gray_bgr_frame)?;
+
+    // Saturation over 1.0 extrapolates away from the gray (clipped into 0 - 255)
+    let mut saturated_frame = Mat::default();
+    core::add_weighted(
+      &adjusted_frame,
+      self.saturation,
+      &gray_bgr_frame,
+      1.0 - self.saturation,
+      0.0,
+      &mut saturated_frame,
+      -1,
+    )?;
+
+    Ok(saturated_frame)
+  }
+
+  /// Computes the lookup table (gamma first, then contrast around the mid gray and brightness)
+  fn update_lut(&mut self) {
+    for (i, value) in self.lut.iter_mut().enumerate() {
+      let gamma_value = (i as f64 / 255.0).powf(1.0 / self.gamma) * 255.0;
+      let adjusted_value = (gamma_value - 128.0) * self.contrast + 128.0 + self.brightness;
+
+      *value = adjusted_value.round().clamp(0.0, 255.0) as u8;
+    }
+  }
+}
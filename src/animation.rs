@@ -0,0 +1,305 @@
+use std::{
+  collections::HashMap,
+  fs::File,
+  io::{BufWriter, Write},
+  path::{Path, PathBuf},
+  sync::mpsc::{Receiver, SyncSender},
+  thread::JoinHandle,
+  time::{Duration, Instant},
+};
+
+use gif::{Encoder, Frame, Repeat};
+use ratatui::text::Text;
+
+use crate::{
+  channel::AppEvent,
+  export::{self, DEFAULT_BG, DEFAULT_FG},
+  font::{self, GLYPH_SIZE},
+  graphics,
+  handler::{octant_char, sextant_char, BRAILLE_DOTS},
+};
+
+/// Maximal duration of a GIF recording (the recording stops itself)
+pub const GIF_MAX_DURATION: Duration = Duration::from_secs(30);
+
+/// Maximal number of frames waiting for the encoder (newer frames are dropped)
+const GIF_QUEUE_SIZE: usize = 8;
+
+/// Minimal GIF frame delay in centiseconds (smaller delays are slowed down by viewers)
+const GIF_MIN_DELAY: u16 = 2;
+
+/// Delay of the last GIF frame in centiseconds
+const GIF_LAST_DELAY: u16 = 10;
+
+/// Shape of a cell glyph
+#[derive(Clone, Copy)]
+enum GlyphShape {
+  // Bitmap font glyph
+  Font(&'static [u8; 10]),
+
+  // Block of 2 x `rows` subcells (half blocks, quadrants, sextants and octants)
+  Blocks { rows: usize, bits: u8 },
+
+  // Braille dots
+  Braille(u8),
+
+  // Shade (░ ▒ ▓) of 1 - 3 quarters
+  Shade(u8),
+}
+
+/// Animated GIF recorder.
+///
+/// Rendered frames are rasterized and encoded (with a palette per frame) on a separate
+/// thread. The recording ends when the recorder is dropped, the drop waits for the encoder
+/// to finish the file.
+pub struct GifRecorder {
+  tx: Option<SyncSender<(Text<'static>, Instant)>>,
+  handle: Option<JoinHandle<()>>,
+  started_at: Instant,
+}
+
+impl GifRecorder {
+  /// Starts an encoder thread (the file is created with the first frame).
+  ///
+  /// Reports the result as a toast when the recording ends.
+  pub fn start(path: PathBuf, app_tx: tokio::sync::mpsc::UnboundedSender<AppEvent>) -> Self {
+    let (tx, rx) = std::sync::mpsc::sync_channel(GIF_QUEUE_SIZE);
+
+    let handle = std::thread::spawn(move || {
+      let message = match write_gif(&path, rx) {
+        Ok(frame_count) => format!("GIF saved to {} ({} frames)", path.display(), frame_count),
+        Err(err) => format!("GIF recording failed: {}", err),
+      };
+
+      let _ = app_tx.send(AppEvent::Toast(message));
+    });
+
+    Self {
+      tx: Some(tx),
+      handle: Some(handle),
+      started_at: Instant::now(),
+    }
+  }
+
+  /// Queues a rendered frame (dropped if the encoder lags behind)
+  pub fn push(&self, text: &Text<'static>) {
+    if let Some(tx) = self.tx.as_ref() {
+      let _ = tx.try_send((text.clone(), Instant::now()));
+    }
+  }
+
+  /// Returns the recording duration
+  pub fn elapsed(&self) -> Duration {
+    self.started_at.elapsed()
+  }
+
+  /// Checks if the recording reached the maximal duration
+  pub fn is_expired(&self) -> bool {
+    self.elapsed() >= GIF_MAX_DURATION
+  }
+}
+
+impl Drop for GifRecorder {
+  /// Closes the queue and waits until the encoder flushes the file
+  fn drop(&mut self) {
+    drop(self.tx.take());
+
+    if let Some(handle) = self.handle.take() {
+      let _ = handle.join();
+    }
+  }
+}
+
+/// Encodes received frames into the GIF file, returns the number of written frames.
+///
+/// A frame is written when the next one arrives, so its delay is known.
+fn write_gif(
+  path: &Path,
+  rx: Receiver<(Text<'static>, Instant)>,
+) -> Result<usize, Box<dyn std::error::Error>> {
+  if let Some(dir) = path.parent() {
+    std::fs::create_dir_all(dir)?;
+  }
+
+  let glyph_shapes = block_glyph_shapes();
+
+  let mut encoder: Option<Encoder<BufWriter<File>>> = None;
+  let mut size = (0, 0);
+  let mut pending_frame: Option<(Frame<'static>, Instant)> = None;
+  let mut frame_count = 0;
+
+  for (text, received_at) in rx {
+    // Graphics protocol modes have no rendered characters
+    if text.lines.is_empty() {
+      continue;
+    }
+
+    // The canvas size is fixed by the first frame
+    if encoder.is_none() {
+      size = canvas_size(&text);
+
+      let file = BufWriter::new(File::create(path)?);
+      let mut gif_encoder = Encoder::new(file, size.0, size.1, &[])?;
+      gif_encoder.set_repeat(Repeat::Infinite)?;
+
+      encoder = Some(gif_encoder);
+    }
+
+    let Some(encoder) = encoder.as_mut() else {
+      continue;
+    };
+
+    if let Some((mut frame, pending_at)) = pending_frame.take() {
+      let delay = (received_at - pending_at).as_millis() / 10;
+      frame.delay = (delay as u16).max(GIF_MIN_DELAY);
+      encoder.write_frame(&frame)?;
+      frame_count += 1;
+    }
+
+    pending_frame = Some((rasterize(&text, size, &glyph_shapes), received_at));
+  }
+
+  let Some(mut encoder) = encoder else {
+    return Ok(frame_count);
+  };
+
+  if let Some((mut frame, _)) = pending_frame {
+    frame.delay = GIF_LAST_DELAY;
+    encoder.write_frame(&frame)?;
+    frame_count += 1;
+  }
+
+  encoder.into_inner()?.flush()?;
+
+  Ok(frame_count)
+}
+
+/// Returns a canvas size of the rendered frame in pixels
+fn canvas_size(text: &Text) -> (u16, u16) {
+  let cols = text
+    .lines
+    .iter()
+    .map(|line| line.width())
+    .max()
+    .unwrap_or(0);
+  let rows = text.lines.len();
+
+  ((cols * GLYPH_SIZE.0) as u16, (rows * GLYPH_SIZE.1) as u16)
+}
+
+/// Rasterizes the rendered frame into an indexed GIF frame.
+///
+/// The canvas size is fixed, cells outside of it are cropped.
+fn rasterize(
+  text: &Text,
+  (width, height): (u16, u16),
+  glyph_shapes: &HashMap<char, GlyphShape>,
+) -> Frame<'static> {
+  let (width, height) = (width as usize, height as usize);
+  let (cell_width, cell_height) = GLYPH_SIZE;
+
+  let mut rgb = [DEFAULT_BG.0, DEFAULT_BG.1, DEFAULT_BG.2].repeat(width * height);
+
+  for (row, line) in text.lines.iter().enumerate() {
+    let mut col = 0;
+
+    for span in &line.spans {
+      let style = line.style.patch(span.style);
+      let fg = style.fg.and_then(export::color_rgb).unwrap_or(DEFAULT_FG);
+      let bg = style.bg.and_then(export::color_rgb).unwrap_or(DEFAULT_BG);
+
+      for c in span.content.chars() {
+        let shape = glyph_shape(c, glyph_shapes);
+
+        for y in 0..cell_height {
+          for x in 0..cell_width {
+            let (px, py) = (col * cell_width + x, row * cell_height + y);
+
+            if px >= width || py >= height {
+              continue;
+            }
+
+            let (r, g, b) = if shape.is_some_and(|shape| is_lit(shape, x, y)) {
+              fg
+            } else {
+              bg
+            };
+
+            let offset = (py * width + px) * 3;
+            rgb[offset..offset + 3].copy_from_slice(&[r, g, b]);
+          }
+        }
+
+        col += 1;
+      }
+    }
+  }
+
+  let (palette, indices) = graphics::quantize(&rgb, 256);
+
+  Frame {
+    width: width as u16,
+    height: height as u16,
+    palette: Some(palette.concat()),
+    buffer: indices.into(),
+    ..Default::default()
+  }
+}
+
+/// Returns a shape of the glyph (None for unknown glyphs, drawn as the background)
+fn glyph_shape(c: char, glyph_shapes: &HashMap<char, GlyphShape>) -> Option<GlyphShape> {
+  if let Some(glyph) = font::glyph(c) {
+    return Some(GlyphShape::Font(glyph));
+  }
+
+  match c {
+    '\u{2800}'..='\u{28FF}' => Some(GlyphShape::Braille((c as u32 - 0x2800) as u8)),
+    '░' => Some(GlyphShape::Shade(1)),
+    '▒' => Some(GlyphShape::Shade(2)),
+    '▓' => Some(GlyphShape::Shade(3)),
+    _ => glyph_shapes.get(&c).copied(),
+  }
+}
+
+/// Returns shapes of all block glyphs drawn by the block camera modes
+fn block_glyph_shapes() -> HashMap<char, GlyphShape> {
+  let mut glyph_shapes = HashMap::new();
+
+  // Octant patterns include the half blocks and quadrants too
+  for bits in 0..=u8::MAX {
+    glyph_shapes.insert(octant_char(bits), GlyphShape::Blocks { rows: 4, bits });
+  }
+
+  for bits in 0..64 {
+    glyph_shapes
+      .entry(sextant_char(bits))
+      .or_insert(GlyphShape::Blocks { rows: 3, bits });
+  }
+
+  glyph_shapes
+}
+
+/// Checks if a pixel of the glyph cell is drawn by the foreground color
+fn is_lit(shape: GlyphShape, x: usize, y: usize) -> bool {
+  let (cell_width, cell_height) = GLYPH_SIZE;
+
+  match shape {
+    GlyphShape::Font(glyph) => font::is_set(glyph, x, y),
+    GlyphShape::Blocks { rows, bits } => {
+      let subcell = (y * rows / cell_height) * 2 + x * 2 / cell_width;
+      bits & (1 << subcell) != 0
+    }
+    // 2x2 pixel dots on a 3 x 2.5 pixel grid
+    GlyphShape::Braille(bits) => BRAILLE_DOTS.iter().enumerate().any(|(i, (dx, dy))| {
+      let dot_x = *dx as usize * cell_width / 2 + 1;
+      let dot_y = *dy as usize * cell_height / 4;
+
+      bits & (1 << i) != 0 && (dot_x..dot_x + 2).contains(&x) && (dot_y..dot_y + 2).contains(&y)
+    }),
+    GlyphShape::Shade(quarters) => match quarters {
+      1 => x & 1 == 0 && y & 1 == 0,
+      2 => (x + y) & 1 == 0,
+      _ => x & 1 == 0 || y & 1 == 0,
+    },
+  }
+}
@@ -1,12 +1,16 @@
-use std::sync::Arc;
-use crossterm::event::KeyModifiers;
-use tokio::sync::RwLock;
+use std::{
+  io::Write,
+  sync::Arc,
+  time::{Duration, Instant},
+};
+use crossterm::{cursor::MoveTo, queue};
+use tokio::{sync::RwLock, task::JoinHandle};
 
 use crate::{channel::AppEvent, handler::CamWindowScale};
 
 use ratatui::{
-  crossterm::event::KeyCode,
-  layout::{Alignment, Constraint, Direction, Flex, Layout},
+  crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers},
+  layout::{Alignment, Constraint, Direction, Flex, Layout, Rect},
   style::{Color, Style, Stylize},
   text::{Line, Span, Text},
   widgets::{Block, BorderType, Clear, Paragraph},
@@ -14,15 +18,30 @@ use ratatui::{
 };
 
 use crate::{
+  animation::GifRecorder,
+  cast::{self, CastRecorder},
   channel::Channel,
-  handler::{EventHandler, FrameHandler, FrameHandlerConfig, ImageConvertType},
+  cli::Options,
+  config::ConfigFile,
+  dither::Dithering,
+  export,
+  graphics::GraphicsProtocol,
+  handler::{CameraWatcher, EventHandler, FrameHandler, FrameHandlerConfig, ImageConvertType},
+  keymap::{Action, ActionGroup, Keymap},
+  panel::{AdjustmentsPanel, ControlsPanel},
+  picker::CameraPicker,
+  snapshot,
+  source,
 };
 
-/// Camera TUI frame border color
-const PRIMARY_COLOR: Color = Color::Rgb(230, 143, 106);
+/// Default camera TUI frame border color (overridden by the config file)
+pub const DEFAULT_BORDER_COLOR: Color = Color::Rgb(230, 143, 106);
 
 pub const ASCII_CHARS: &[char] = &['█', '▓', '▒', '░', ' '];
 
+/// How long a toast message stays in place of the help bar
+const TOAST_DURATION: Duration = Duration::from_secs(3);
+
 pub struct App<'a> {
   // Base terminal
   terminal: &'a mut DefaultTerminal,
@@ -33,8 +52,47 @@ pub struct App<'a> {
   // Frame buffer (video buffer)
   frame_buffer: Text<'static>,
 
+  // Graphics protocol image waiting to be drawn
+  image_buffer: Option<Vec<u8>>,
+
+  // Graphics protocol of the image drawn in the camera area
+  shown_image: Option<GraphicsProtocol>,
+
+  // Toast message and the time it was received
+  toast: Option<(String, Instant)>,
+
+  // Active asciicast recording of the rendered frames
+  cast_recorder: Option<CastRecorder>,
+
+  // Active animated GIF recording of the rendered frames
+  gif_recorder: Option<GifRecorder>,
+
+  // Frame handler task (awaited on exit, so a video recording is finished)
+  frame_handler: JoinHandle<()>,
+
   // Frame handler config (for a switchable image proccessing modes)
   frame_handler_config: Arc<RwLock<FrameHandlerConfig>>,
+
+  // Border color of the camera block and the help bar
+  border_color: Color,
+
+  // Config file (the last used settings are saved on exit if enabled)
+  config_file: ConfigFile,
+
+  // Key bindings of the user actions
+  keymap: Keymap,
+
+  // Frame pipeline error shown in the camera block (until a retry or a camera switch)
+  error: Option<String>,
+
+  // Open camera picker popup
+  camera_picker: Option<CameraPicker>,
+
+  // Open camera controls panel
+  controls_panel: Option<ControlsPanel>,
+
+  // Open image adjustments panel
+  adjustments_panel: Option<AdjustmentsPanel>,
 }
 
 impl<'a> App<'a> {
@@ -44,24 +102,62 @@ impl<'a> App<'a> {
   /// Try to creates a frame handler and event handler
   pub async fn try_new(
     terminal: &'a mut DefaultTerminal,
+    mut options: Options,
+    config_file: ConfigFile,
   ) -> Result<Self, Box<dyn std::error::Error>> {
     let mut channel = Channel::new();
     let terminal_size = terminal.size()?;
+    let border_color = options.border_color;
+    let keymap = std::mem::take(&mut options.keymap);
+    let requested_mode = options.image_convert_type.clone();
+
+    let frame_handler_config = Arc::new(RwLock::new(FrameHandlerConfig::try_new(
+      terminal_size,
+      options,
+    )?));
+
+    let toast = {
+      let config = frame_handler_config.read().await;
+
+      (config.image_convert_type != requested_mode).then(|| {
+        let message = format!(
+          "Mode {} is not supported by the terminal, using {}",
+          requested_mode.name(),
+          config.image_convert_type.name()
+        );
 
-    let frame_handler_config = Arc::new(RwLock::new(FrameHandlerConfig::new(terminal_size)));
+        (message, Instant::now())
+      })
+    };
+
+    let source = source::from_input(frame_handler_config.read().await.input.as_deref());
 
     let frame_handler =
-      FrameHandler::try_new(frame_handler_config.clone(), channel.get_tx()).await?;
+      FrameHandler::try_new(frame_handler_config.clone(), channel.get_tx(), source).await?;
 
-    frame_handler.run().await?;
+    let frame_handler = frame_handler.run().await;
 
     let _event_handler = EventHandler::new(channel.get_tx());
+    let _camera_watcher = CameraWatcher::new(frame_handler_config.clone(), channel.get_tx());
 
     Ok(Self {
       terminal,
       channel,
       frame_buffer: Text::default(),
+      image_buffer: None,
+      shown_image: None,
+      toast,
+      cast_recorder: None,
+      gif_recorder: None,
+      frame_handler,
       frame_handler_config,
+      border_color,
+      config_file,
+      keymap,
+      error: None,
+      camera_picker: None,
+      controls_panel: None,
+      adjustments_panel: None,
     })
   }
 
@@ -77,26 +173,98 @@ impl<'a> App<'a> {
       let terminal_size = self.terminal.size()?;
 
       if let Some(app_event) = self.channel.next().await {
+        // A key may change a setting or remove the shown image, so the frame handler
+        // converts the last frame again (e.g. of a paused video)
+        let is_redraw_needed =
+          matches!(app_event, AppEvent::Event(_) | AppEvent::TerminalResize(_));
+
         match app_event {
-          AppEvent::AsciiFrame(ascii_frame) => self.frame_buffer = ascii_frame,
+          AppEvent::AsciiFrame(ascii_frame) => {
+            // The frame source recovered (e.g. a reconnected camera)
+            self.error = None;
+            self.frame_buffer = ascii_frame;
+            self.clear_image()?;
+            self.record_cast_frame();
+            self.record_gif_frame();
+          }
+          AppEvent::ImageFrame(graphics_protocol, image) => {
+            self.error = None;
+
+            if self.shown_image != Some(graphics_protocol) {
+              self.clear_image()?;
+            }
+
+            self.frame_buffer = Text::default();
+            self.image_buffer = Some(image);
+            self.shown_image = Some(graphics_protocol);
+          }
+          // The camera picker takes the keys while open
+          AppEvent::Event(key_event) if self.camera_picker.is_some() => {
+            self.handle_picker_key(&key_event).await;
+          }
+          // The controls panel takes the keys while open
+          AppEvent::Event(key_event) if self.controls_panel.is_some() => {
+            self.handle_controls_key(&key_event).await;
+          }
+          // The adjustments panel takes the keys while open
+          AppEvent::Event(key_event) if self.adjustments_panel.is_some() => {
+            self.handle_adjustments_key(&key_event).await;
+          }
           AppEvent::Event(key_event) => {
-            if key_event.modifiers.contains(KeyModifiers::CONTROL) && key_event.code == KeyCode::Char(' ') {
-              self.toggle_lock().await;
+            let Some(action) = self.keymap.handle(&key_event) else {
+              continue;
+            };
+
+            // Only the lock toggle works while locked
+            if action != Action::ToggleLock && self.frame_handler_config.read().await.is_locked {
+              continue;
             }
 
-            if !self.frame_handler_config.read().await.is_locked {
-              match key_event.code {
-                KeyCode::Char('m') => self.switch_mode().await,
-                KeyCode::Char('f') => self.switch_cam_window_scale().await,
-                KeyCode::Char('c') => self.switch_cam().await,
-                KeyCode::Esc => break,
-                _ => {}
-              }
+            match action {
+              Action::Exit => break,
+              Action::SwitchMode => self.switch_mode().await,
+              Action::SwitchCamera => self.switch_cam().await,
+              Action::PickCamera => self.open_camera_picker().await?,
+              Action::SwitchCaptureMode => self.switch_capture_mode().await,
+              Action::ToggleControls => self.open_controls_panel().await?,
+              Action::ToggleAdjustments => self.open_adjustments_panel()?,
+              Action::ToggleFullscreen => self.switch_cam_window_scale().await,
+              Action::Snapshot => self.take_snapshot().await,
+              Action::Export => self.export_frame().await,
+              Action::RecordVideo => self.toggle_video_recording().await,
+              Action::RecordCast => self.toggle_cast_recording().await,
+              Action::RecordGif => self.toggle_gif_recording().await,
+              Action::ToggleLock => self.toggle_lock().await,
+              Action::SwitchRamp => self.switch_ascii_ramp().await,
+              Action::InvertRamp => self.invert_ascii_ramp().await,
+              Action::SwitchThreshold => self.switch_threshold_method().await,
+              Action::LowerThreshold => self.adjust_threshold(-1).await,
+              Action::RaiseThreshold => self.adjust_threshold(1).await,
+              Action::SwitchDithering => self.switch_dithering().await,
+              Action::TogglePause
+              | Action::SeekBackward
+              | Action::SeekForward
+              | Action::SlowDown
+              | Action::SpeedUp
+              | Action::ToggleLoop => self.control_playback(action).await,
+              Action::Retry => self.retry().await,
             }
-          },
+          }
           AppEvent::TerminalResize((width, height)) => {
+            // A cast recording is resized by the next rendered frame
             self.frame_handler_config.write().await.terminal_size = (width, height);
           }
+          AppEvent::Toast(message) => {
+            self.toast = Some((message, Instant::now()));
+          }
+          AppEvent::Error(err) => {
+            self.error = Some(err.to_string());
+            self.clear_image()?;
+          }
+        }
+
+        if is_redraw_needed {
+          self.frame_handler_config.write().await.is_redraw_requested = true;
         }
       }
 
@@ -109,12 +277,140 @@ impl<'a> App<'a> {
 
       let is_locked = self.frame_handler_config.read().await.is_locked;
 
+      let image_convert_type = self
+        .frame_handler_config
+        .read()
+        .await
+        .image_convert_type
+        .clone();
+      let is_ascii = image_convert_type == ImageConvertType::Ascii;
+      let is_threshold = image_convert_type.is_threshold();
+      let is_ditherable = image_convert_type.is_ditherable();
+
+      let (cam_title, is_playback) = self.get_cam_title().await;
+
+      let mut cam_title_spans = vec![Span::from(cam_title)];
+
+      let recording_started_at = self.frame_handler_config.read().await.recording_started_at;
+
+      if let Some(recording_started_at) = recording_started_at {
+        cam_title_spans.push(recording_span("REC", recording_started_at.elapsed()));
+      }
+
+      if let Some(cast_recorder) = self.cast_recorder.as_ref() {
+        cam_title_spans.push(recording_span("CAST", cast_recorder.elapsed()));
+      }
+
+      if let Some(gif_recorder) = self.gif_recorder.as_ref() {
+        cam_title_spans.push(recording_span("GIF", gif_recorder.elapsed()));
+      }
+
+      if self
+        .toast
+        .as_ref()
+        .is_some_and(|(_, received_at)| received_at.elapsed() > TOAST_DURATION)
+      {
+        self.toast = None;
+      }
+
+      let toast = self.toast.as_ref().map(|(message, _)| message.clone());
+
+      let border_color = self.border_color;
+
+      let help = [
+        ActionGroup::General,
+        ActionGroup::Ascii,
+        ActionGroup::Threshold,
+        ActionGroup::Dithering,
+        ActionGroup::Playback,
+      ]
+      .map(|group| self.keymap.help(group));
+
+      let error_banner = self.error.as_ref().map(|error| {
+        let mut error_help = self.keymap.help(ActionGroup::Error);
+
+        if let Some(label) = self.keymap.label(Action::PickCamera) {
+          error_help.push((label, Action::PickCamera.description()));
+        }
+
+        let mut banner_lines = vec![Line::from(error.clone()).bold()];
+        banner_lines.extend(help_lines(
+          &error_help,
+          Color::Red,
+          terminal_size.width.saturating_sub(4),
+        ));
+
+        Text::from(banner_lines)
+      });
+
+      let picker_hint = panel_hint(
+        &self.keymap,
+        "↑/↓ select | enter pick",
+        &[],
+        Action::PickCamera,
+      );
+
+      let camera_picker = self.camera_picker.as_ref().map(|camera_picker| {
+        let widget = camera_picker.widget(border_color, picker_hint);
+        (camera_picker.size(), widget)
+      });
+
+      let controls_hint = panel_hint(
+        &self.keymap,
+        "↑/↓ select | ←/→ adjust",
+        &[Action::ResetSelected, Action::SavePreset],
+        Action::ToggleControls,
+      );
+
+      let adjustments_hint = panel_hint(
+        &self.keymap,
+        "↑/↓ select | ←/→ adjust",
+        &[Action::ResetSelected, Action::ResetAll],
+        Action::ToggleAdjustments,
+      );
+
+      let controls_panel = match self.controls_panel.as_ref() {
+        Some(controls_panel) => {
+          let config = self.frame_handler_config.read().await;
+          Some(controls_panel.text(&config, border_color))
+        }
+        None => None,
+      };
+
+      let adjustments_panel = match self.adjustments_panel.as_ref() {
+        Some(adjustments_panel) => {
+          let config = self.frame_handler_config.read().await;
+          Some(adjustments_panel.text(&config, border_color))
+        }
+        None => None,
+      };
+
+      // Help lines of the active mode and input (wrapped to the terminal width)
+      let mut tools_lines = help_lines(&help[0], border_color, terminal_size.width);
+
+      let shown_groups = [is_ascii, is_threshold, is_ditherable, is_playback];
+
+      for (group_help, _) in help[1..]
+        .iter()
+        .zip(shown_groups)
+        .filter(|(_, is_shown)| *is_shown)
+      {
+        tools_lines.extend(help_lines(group_help, border_color, terminal_size.width));
+      }
+
+      let tools_height = tools_lines.len().max(1) as u16;
+
+      let mut cam_area = Rect::default();
+
       self.terminal.draw(|frame| {
         let area = frame.area();
 
         let chunks = Layout::new(
           Direction::Vertical,
-          [Constraint::Percentage(100), Constraint::Length(2)],
+          [
+            Constraint::Percentage(100),
+            Constraint::Length(tools_height),
+          ],
         )
         .split(area);
 
@@ -122,7 +418,8 @@ impl<'a> App<'a> {
         let bottom_chunk = chunks[1];
 
         let block = Block::bordered()
-          .border_style(Style::default().fg(PRIMARY_COLOR))
+          .border_style(Style::default().fg(border_color))
+          .title(Line::from(cam_title_spans))
           .title_style(Style::default())
           .title_alignment(Alignment::Center)
           .border_type(BorderType::Rounded);
@@ -143,19 +440,9 @@ impl<'a> App<'a> {
         let [top_chunk] = vertical.areas(top_chunk);
         let [top_chunk] = horizontal.areas(top_chunk);
 
-        let tools_text = Text::from(vec![Line::from(vec![
-          Span::from("ESC").bold(),
-          Span::from(" exit | "),
-          Span::from("m").bold(),
-          Span::from(" switch mode | "),
-          Span::from("c").bold(),
-          Span::from(" switch camera | "),
-          Span::from("f").bold(),
-          Span::from(" toggle fullscreen | "),
-          Span::from("ctrl-<space>").bold(),
-          Span::from(" toggle lock"),
-        ])
-        .style(Style::default().fg(PRIMARY_COLOR))]);
+        cam_area = Block::bordered().inner(top_chunk);
+
+        let tools_text = Text::from(tools_lines);
 
         let tools_paragraph = Paragraph::new(tools_text)
           .alignment(Alignment::Center)
@@ -164,29 +451,214 @@ impl<'a> App<'a> {
         frame.render_widget(Clear, top_chunk);
         frame.render_widget(cam_paragraph, top_chunk);
 
-        if !is_locked {
+        if let Some(error_banner) = error_banner {
+          let banner_width = (error_banner.width() as u16 + 4).min(top_chunk.width);
+
+          let banner_height = error_banner.height() as u16 + 2;
+
+          let [banner_area] = Layout::vertical([Constraint::Length(banner_height)])
+            .flex(Flex::Center)
+            .areas(top_chunk);
+          let [banner_area] = Layout::horizontal([Constraint::Length(banner_width)])
+            .flex(Flex::Center)
+            .areas(banner_area);
+
+          let banner_paragraph = Paragraph::new(error_banner)
+            .block(
+              Block::bordered()
+                .border_type(BorderType::Rounded)
+                .title(" Error ")
+                .title_alignment(Alignment::Center),
+            )
+            .style(Style::default().fg(Color::Red))
+            .alignment(Alignment::Center);
+
+          frame.render_widget(Clear, banner_area);
+          frame.render_widget(banner_paragraph, banner_area);
+        }
+
+        if let Some(((width, height), (list, mut list_state))) = camera_picker {
+          let [picker_area] = Layout::vertical([Constraint::Length(height.min(top_chunk.height))])
+            .flex(Flex::Center)
+            .areas(top_chunk);
+          let [picker_area] = Layout::horizontal([Constraint::Length(width.min(top_chunk.width))])
+            .flex(Flex::Center)
+            .areas(picker_area);
+
+          frame.render_widget(Clear, picker_area);
+          frame.render_stateful_widget(list, picker_area, &mut list_state);
+        }
+
+        if let Some((controls_text, camera_name)) = controls_panel {
+          let width = (controls_text.width() as u16 + 4).min(top_chunk.width);
+          let height = (controls_text.height() as u16 + 2).min(top_chunk.height);
+
+          // Aligned to the right, so most of the image stays visible
+          let [panel_area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(top_chunk);
+          let [panel_area] = Layout::horizontal([Constraint::Length(width)])
+            .flex(Flex::End)
+            .areas(panel_area);
+
+          let panel_paragraph = Paragraph::new(controls_text).block(
+            Block::bordered()
+              .border_type(BorderType::Rounded)
+              .border_style(Style::default().fg(border_color))
+              .title(format!(" Controls: {} ", camera_name))
+              .title_bottom(controls_hint),
+          );
+
+          frame.render_widget(Clear, panel_area);
+          frame.render_widget(panel_paragraph, panel_area);
+        }
+
+        if let Some(adjustments_text) = adjustments_panel {
+          let width = (adjustments_text.width() as u16 + 4).min(top_chunk.width);
+          let height = (adjustments_text.height() as u16 + 2).min(top_chunk.height);
+
+          let [panel_area] = Layout::vertical([Constraint::Length(height)])
+            .flex(Flex::Center)
+            .areas(top_chunk);
+          let [panel_area] = Layout::horizontal([Constraint::Length(width)])
+            .flex(Flex::End)
+            .areas(panel_area);
+
+          let panel_paragraph = Paragraph::new(adjustments_text).block(
+            Block::bordered()
+              .border_type(BorderType::Rounded)
+              .border_style(Style::default().fg(border_color))
+              .title(" Image adjustments ")
+              .title_bottom(adjustments_hint),
+          );
+
+          frame.render_widget(Clear, panel_area);
+          frame.render_widget(panel_paragraph, panel_area);
+        }
+
+        if let Some(toast) = toast {
+          let toast_paragraph = Paragraph::new(Line::from(toast).bold())
+            .style(Style::default().fg(border_color))
+            .alignment(Alignment::Center);
+
+          frame.render_widget(toast_paragraph, bottom_chunk);
+        } else if !is_locked {
           frame.render_widget(tools_paragraph, bottom_chunk);
         }
       })?;
+
+      if self.frame_handler_config.read().await.cam_area != cam_area {
+        self.frame_handler_config.write().await.cam_area = cam_area;
+      }
+
+      // A graphics protocol image would cover the camera picker (or a panel)
+      if let Some(image) = self.image_buffer.take().filter(|_| {
+        self.camera_picker.is_none()
+          && self.controls_panel.is_none()
+          && self.adjustments_panel.is_none()
+      }) {
+        self.draw_image(&image, cam_area)?;
+      }
+    }
+
+    self.clear_image()?;
+
+    if let Some(cast_recorder) = self.cast_recorder.take() {
+      cast_recorder.finish()?;
+    }
+
+    if let Some(gif_recorder) = self.gif_recorder.take() {
+      let _ = tokio::task::spawn_blocking(move || drop(gif_recorder)).await;
     }
 
+    // The frame handler finishes a video recording before it ends
+    self.frame_handler_config.write().await.is_exit_requested = true;
+    let _ = (&mut self.frame_handler).await;
+
+    let config = self.frame_handler_config.read().await;
+    self.config_file.update(&config);
+    self.config_file.save_on_exit()?;
+
+    Ok(())
+  }
+
+  /// Draws a graphics protocol image into the camera area
+  fn draw_image(&mut self, image: &[u8], cam_area: Rect) -> std::io::Result<()> {
+    let backend = self.terminal.backend_mut();
+
+    queue!(backend, MoveTo(cam_area.x, cam_area.y))?;
+    backend.write_all(image)?;
+    backend.flush()?;
+
     Ok(())
   }
 
+  /// Removes a drawn graphics protocol image (if any)
+  fn clear_image(&mut self) -> std::io::Result<()> {
+    let Some(graphics_protocol) = self.shown_image.take() else {
+      return Ok(());
+    };
+
+    let backend = self.terminal.backend_mut();
+    backend.write_all(&graphics_protocol.clear_sequence())?;
+    backend.flush()?;
+
+    // Forces a full redraw of the cells below the image
+    self.terminal.clear()
+  }
+
   /// Switches a camera mode.
   ///
   /// Startup mode: Image -> GrayScale -> ASCII
   /// Switch: Image -> GrayScale -> Threshold ->  ASCII
+  /// Graphics protocol modes are included only if the terminal supports them.
   pub async fn switch_mode(&mut self) {
-    let new_image_convert_type = match self.frame_handler_config.read().await.image_convert_type {
+    let mut config = self.frame_handler_config.write().await;
+
+    let new_image_convert_type = match config.image_convert_type {
       ImageConvertType::ColorfulHalfBlock => ImageConvertType::Colorful,
       ImageConvertType::Colorful => ImageConvertType::GrayScale,
       ImageConvertType::GrayScale => ImageConvertType::GrayScaleThreshold,
       ImageConvertType::GrayScaleThreshold => ImageConvertType::Threshold,
-      ImageConvertType::Threshold => ImageConvertType::ColorfulHalfBlock,
+      ImageConvertType::Threshold => ImageConvertType::Ascii,
+      ImageConvertType::Ascii => ImageConvertType::Braille,
+      ImageConvertType::Braille => ImageConvertType::ColorfulBraille,
+      ImageConvertType::ColorfulBraille => ImageConvertType::Sextant,
+      ImageConvertType::Sextant => ImageConvertType::Octant,
+      // Graphics protocol modes follow the text modes
+      ImageConvertType::Octant
+      | ImageConvertType::Kitty
+      | ImageConvertType::Sixel
+      | ImageConvertType::ITerm2 => {
+        let graphics_modes = config
+          .graphics_protocols
+          .iter()
+          .map(|graphics_protocol| graphics_protocol.image_convert_type())
+          .collect::<Vec<_>>();
+
+        let next_mode = match config.image_convert_type {
+          ImageConvertType::Octant => graphics_modes.first(),
+          ref mode => graphics_modes
+            .iter()
+            .skip_while(|graphics_mode| *graphics_mode != mode)
+            .nth(1),
+        };
+
+        next_mode
+          .cloned()
+          .unwrap_or(ImageConvertType::ColorfulHalfBlock)
+      }
     };
 
-    self.frame_handler_config.write().await.image_convert_type = new_image_convert_type;
+    // A cast recording has only the rendered characters
+    if self.cast_recorder.is_some() && new_image_convert_type.graphics_protocol().is_some() {
+      self.toast = Some((
+        String::from("Graphics mode frames are not recorded to the cast"),
+        Instant::now(),
+      ));
+    }
+
+    config.image_convert_type = new_image_convert_type;
   }
 
   /// Toggles lock mode.
@@ -211,8 +683,471 @@ impl<'a> App<'a> {
     self.frame_handler_config.write().await.cam_window_scale = cam_window_scale;
   }
 
-  /// Switches a device camera
+  /// Switches a device camera (or a test pattern)
   pub async fn switch_cam(&mut self) {
-    self.frame_handler_config.write().await.camera.switch();
+    let mut config = self.frame_handler_config.write().await;
+    config.switch_input();
+
+    // A failed source is reopened with the switched input
+    if self.error.take().is_some() {
+      config.is_retry_requested = true;
+    }
+  }
+
+  /// Switches a capture mode of the active camera (the automatic one or a fixed one)
+  pub async fn switch_capture_mode(&mut self) {
+    let mut config = self.frame_handler_config.write().await;
+
+    if config.input.is_some() || config.pattern.is_some() || !config.camera.is_connected() {
+      return;
+    }
+
+    let message = match config.camera.switch_capture_mode() {
+      Some(capture_mode) => format!("Capture mode {}", capture_mode),
+      None => String::from("Capture mode auto (the smallest covering the camera window)"),
+    };
+
+    self.toast = Some((message, Instant::now()));
+  }
+
+  /// Opens a camera picker popup (a video file input can't be switched)
+  pub async fn open_camera_picker(&mut self) -> std::io::Result<()> {
+    let config = self.frame_handler_config.read().await;
+
+    if config.input.is_some() {
+      return Ok(());
+    }
+
+    self.camera_picker = Some(CameraPicker::new(&config));
+    drop(config);
+
+    self.clear_image()
   }
+
+  /// Moves the camera picker selection, picks the selected input or closes the picker
+  async fn handle_picker_key(&mut self, key_event: &KeyEvent) {
+    if key_event.kind != KeyEventKind::Press {
+      return;
+    }
+
+    let Some(camera_picker) = self.camera_picker.as_mut() else {
+      return;
+    };
+
+    // The picker closes on its own toggle as well
+    if matches!(
+      self.keymap.handle_panel(key_event),
+      Some(Action::Exit | Action::PickCamera)
+    ) {
+      self.camera_picker = None;
+      return;
+    }
+
+    match key_event.code {
+      KeyCode::Up | KeyCode::Char('k') => camera_picker.select_previous(),
+      KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => camera_picker.select_next(),
+      KeyCode::Enter => {
+        let entry = camera_picker.selected();
+        self.camera_picker = None;
+
+        let mut config = self.frame_handler_config.write().await;
+        entry.apply(&mut config);
+
+        // A failed source is reopened with the picked input
+        if self.error.take().is_some() {
+          config.is_retry_requested = true;
+        }
+      }
+      _ => {}
+    }
+  }
+
+  /// Opens a camera controls panel (of a device camera only)
+  pub async fn open_controls_panel(&mut self) -> std::io::Result<()> {
+    let config = self.frame_handler_config.read().await;
+
+    if config.input.is_some() || config.pattern.is_some() || !config.camera.is_connected() {
+      self.toast = Some((
+        String::from("Camera controls are available for a device camera only"),
+        Instant::now(),
+      ));
+      return Ok(());
+    }
+
+    self.controls_panel = Some(ControlsPanel::new(&config));
+    drop(config);
+
+    self.clear_image()
+  }
+
+  /// Moves the controls panel selection, adjusts the selected control or saves a preset
+  async fn handle_controls_key(&mut self, key_event: &KeyEvent) {
+    if key_event.kind != KeyEventKind::Press {
+      return;
+    }
+
+    let Some(controls_panel) = self.controls_panel.as_mut() else {
+      return;
+    };
+
+    let mut config = self.frame_handler_config.write().await;
+    let is_fine = key_event.modifiers.contains(KeyModifiers::SHIFT);
+
+    match self.keymap.handle_panel(key_event) {
+      Some(Action::ResetSelected) => return controls_panel.reset(&mut config),
+      Some(Action::SavePreset) => {
+        let Some(camera_name) = config.save_camera_preset() else {
+          return;
+        };
+
+        let message = match self.config_file.save_presets(&config.camera_presets) {
+          Ok(path) => format!("Preset of {} saved to {}", camera_name, path.display()),
+          Err(err) => format!("Preset not saved: {}", err),
+        };
+
+        self.toast = Some((message, Instant::now()));
+        return;
+      }
+      Some(Action::Exit | Action::ToggleControls) => {
+        self.controls_panel = None;
+        return;
+      }
+      _ => {}
+    }
+
+    match key_event.code {
+      KeyCode::Up | KeyCode::Char('k') => controls_panel.select(&config, -1),
+      KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => controls_panel.select(&config, 1),
+      KeyCode::Left | KeyCode::Char('h') => controls_panel.adjust(&mut config, -1.0, is_fine),
+      KeyCode::Right | KeyCode::Char('l') => controls_panel.adjust(&mut config, 1.0, is_fine),
+      KeyCode::Char(' ') | KeyCode::Enter => controls_panel.adjust(&mut config, 1.0, false),
+      _ => {}
+    }
+  }
+
+  /// Opens an image adjustments panel (of any input)
+  pub fn open_adjustments_panel(&mut self) -> std::io::Result<()> {
+    self.adjustments_panel = Some(AdjustmentsPanel::new());
+    self.clear_image()
+  }
+
+  /// Moves the adjustments panel selection or adjusts the selected adjustment
+  async fn handle_adjustments_key(&mut self, key_event: &KeyEvent) {
+    if key_event.kind != KeyEventKind::Press {
+      return;
+    }
+
+    let Some(adjustments_panel) = self.adjustments_panel.as_mut() else {
+      return;
+    };
+
+    let mut config = self.frame_handler_config.write().await;
+
+    match self.keymap.handle_panel(key_event) {
+      Some(Action::ResetSelected) => return adjustments_panel.reset(&mut config),
+      Some(Action::ResetAll) => return config.image_adjustments.reset(),
+      Some(Action::Exit | Action::ToggleAdjustments) => {
+        self.adjustments_panel = None;
+        return;
+      }
+      _ => {}
+    }
+
+    match key_event.code {
+      KeyCode::Up | KeyCode::Char('k') => adjustments_panel.select(-1),
+      KeyCode::Down | KeyCode::Char('j') | KeyCode::Tab => adjustments_panel.select(1),
+      KeyCode::Left | KeyCode::Char('h') => adjustments_panel.adjust(&mut config, -1.0),
+      KeyCode::Right | KeyCode::Char('l') => adjustments_panel.adjust(&mut config, 1.0),
+      _ => {}
+    }
+  }
+
+  /// Reopens a failed frame source
+  pub async fn retry(&mut self) {
+    if self.error.take().is_some() {
+      self.frame_handler_config.write().await.is_retry_requested = true;
+    }
+  }
+
+  /// Switches a character ramp of the ASCII art mode
+  pub async fn switch_ascii_ramp(&mut self) {
+    self.frame_handler_config.write().await.ascii_ramp.switch();
+  }
+
+  /// Inverts a character ramp of the ASCII art mode (for light backgrounds)
+  pub async fn invert_ascii_ramp(&mut self) {
+    self.frame_handler_config.write().await.ascii_ramp.toggle_invert();
+  }
+
+  /// Switches a method of the threshold modes (fixed, Otsu, adaptive)
+  pub async fn switch_threshold_method(&mut self) {
+    self.frame_handler_config.write().await.threshold.switch();
+  }
+
+  /// Adjusts a value (or an adaptive block size) of the threshold modes
+  pub async fn adjust_threshold(&mut self, direction: i32) {
+    self.frame_handler_config.write().await.threshold.adjust(direction);
+  }
+
+  /// Switches a dithering of the threshold modes and the Sixel palette
+  pub async fn switch_dithering(&mut self) {
+    let mut config = self.frame_handler_config.write().await;
+    config.dithering = config.dithering.next();
+  }
+
+  /// Requests a snapshot of the next raw frame (saved by the frame handler)
+  pub async fn take_snapshot(&mut self) {
+    self.frame_handler_config.write().await.is_snapshot_requested = true;
+  }
+
+  /// Exports the rendered frame as ANSI, plain text, HTML and SVG files.
+  ///
+  /// Graphics protocol modes have no rendered characters to export.
+  pub async fn export_frame(&mut self) {
+    if self.frame_buffer.lines.is_empty() {
+      self.toast = Some((String::from("Nothing to export in a graphics mode"), Instant::now()));
+      return;
+    }
+
+    let path = snapshot::timestamped_path(&self.frame_handler_config.read().await.snapshot_dir);
+
+    let message = match export::export_all(&self.frame_buffer, &path) {
+      Ok(_) => format!("Frame exported to {}.{{ans,txt,html,svg}}", path.display()),
+      Err(err) => format!("Export failed: {}", err),
+    };
+
+    self.toast = Some((message, Instant::now()));
+  }
+
+  /// Starts or stops a video recording (written by the frame handler)
+  pub async fn toggle_video_recording(&mut self) {
+    let mut config = self.frame_handler_config.write().await;
+    config.is_recording = !config.is_recording;
+  }
+
+  /// Starts or stops an asciicast recording of the rendered frames
+  pub async fn toggle_cast_recording(&mut self) {
+    let message = match self.cast_recorder.take() {
+      Some(cast_recorder) => match cast_recorder.finish() {
+        Ok(path) => format!("Recording saved to {}", path.display()),
+        Err(err) => format!("Recording failed: {}", err),
+      },
+      None => {
+        let config = self.frame_handler_config.read().await;
+        let path = snapshot::timestamped_path(&config.snapshot_dir).with_extension("cast");
+
+        // Sized by the rendered frame, so the recording doesn't start with a resize
+        let size = if self.frame_buffer.lines.is_empty() {
+          let cam_window_scale = config.cam_window_scale.clone() as u16;
+          (
+            config.terminal_size.0 / cam_window_scale,
+            config.terminal_size.1 / cam_window_scale,
+          )
+        } else {
+          cast::frame_size(&self.frame_buffer)
+        };
+
+        match CastRecorder::create(&path, size) {
+          Ok(cast_recorder) => {
+            self.cast_recorder = Some(cast_recorder);
+
+            if config.image_convert_type.graphics_protocol().is_some() {
+              format!(
+                "Recording to {} (graphics mode frames are not recorded)",
+                path.display()
+              )
+            } else {
+              format!("Recording to {}", path.display())
+            }
+          }
+          Err(err) => format!("Recording failed: {}", err),
+        }
+      }
+    };
+
+    self.toast = Some((message, Instant::now()));
+  }
+
+  /// Writes the rendered frame into the active asciicast recording.
+  ///
+  /// The recording is stopped if the file can't be written.
+  fn record_cast_frame(&mut self) {
+    let Some(cast_recorder) = self.cast_recorder.as_mut() else {
+      return;
+    };
+
+    if let Err(err) = cast_recorder.write_frame(&self.frame_buffer) {
+      self.cast_recorder = None;
+      self.toast = Some((format!("Recording failed: {}", err), Instant::now()));
+    }
+  }
+
+  /// Starts or stops an animated GIF recording of the rendered frames.
+  ///
+  /// The encoder reports the saved file as a toast.
+  pub async fn toggle_gif_recording(&mut self) {
+    if let Some(gif_recorder) = self.gif_recorder.take() {
+      finish_gif_recording(gif_recorder);
+      return;
+    }
+
+    let path = snapshot::timestamped_path(&self.frame_handler_config.read().await.snapshot_dir)
+      .with_extension("gif");
+
+    self.toast = Some((format!("Recording GIF to {}", path.display()), Instant::now()));
+    self.gif_recorder = Some(GifRecorder::start(path, self.channel.get_tx()));
+  }
+
+  /// Queues the rendered frame into the active GIF recording.
+  ///
+  /// The recording is stopped when it reaches the maximal duration.
+  fn record_gif_frame(&mut self) {
+    let Some(gif_recorder) = self.gif_recorder.as_ref() else {
+      return;
+    };
+
+    if gif_recorder.is_expired() {
+      if let Some(gif_recorder) = self.gif_recorder.take() {
+        finish_gif_recording(gif_recorder);
+      }
+      return;
+    }
+
+    gif_recorder.push(&self.frame_buffer);
+  }
+
+  /// Controls a video file playback
+  pub async fn control_playback(&mut self, action: Action) {
+    let playback = &mut self.frame_handler_config.write().await.playback;
+
+    match action {
+      Action::TogglePause => playback.toggle_pause(),
+      Action::ToggleLoop => playback.toggle_loop(),
+      Action::SpeedUp => playback.speed_up(),
+      Action::SlowDown => playback.slow_down(),
+      Action::SeekForward => playback.seek_forward(),
+      Action::SeekBackward => playback.seek_backward(),
+      _ => {}
+    }
+  }
+
+  /// Returns a camera block title with the frame source description.
+  ///
+  /// Includes the ASCII art ramp in the ASCII mode, the threshold (or the dithering)
+  /// in the threshold modes and the playback state
+  /// if a video file is used (returned as a flag).
+  async fn get_cam_title(&self) -> (String, bool) {
+    let config = self.frame_handler_config.read().await;
+
+    let mut description = config.source_description.clone();
+
+    if config.input.is_none() && config.pattern.is_none() && !config.camera.is_connected() {
+      description += " | disconnected, waiting for reconnect";
+    }
+
+    if config.image_convert_type == ImageConvertType::Ascii {
+      let ascii_ramp = &config.ascii_ramp;
+      let inverted = if ascii_ramp.is_inverted() { " inverted" } else { "" };
+      description += &format!(" | {} ramp{}", ascii_ramp.kind().name(), inverted);
+    }
+
+    // Dithering replaces the threshold
+    if config.image_convert_type.is_ditherable() && config.dithering != Dithering::None {
+      description += &format!(" | {} dithering", config.dithering.name());
+    } else if config.image_convert_type.is_threshold() {
+      description += &format!(" | {}", config.threshold.describe());
+    }
+
+    if !config.input.as_deref().is_some_and(|input| !source::is_image(input)) {
+      return (format!(" {} ", description), false);
+    }
+
+    let state = if config.playback.is_paused { "⏸" } else { "▶" };
+    let looping = if config.playback.is_looping { " | loop" } else { "" };
+
+    (
+      format!(
+        " {} | {} {}x{} ",
+        description,
+        state,
+        config.playback.speed(),
+        looping
+      ),
+      true,
+    )
+  }
+}
+
+/// Returns help bar lines of the key bindings (`key description | key description`),
+/// wrapped between the bindings to fit the width
+fn help_lines(help: &[(String, &'static str)], color: Color, width: u16) -> Vec<Line<'static>> {
+  let mut lines = Vec::new();
+  let mut spans = Vec::new();
+  let mut line_width = 0;
+
+  for (label, description) in help {
+    let label = Span::from(label.clone()).bold();
+    let description = Span::from(format!(" {}", description));
+    let entry_width = label.width() + description.width();
+
+    if !spans.is_empty() && line_width + 3 + entry_width > width as usize {
+      lines.push(Line::from(std::mem::take(&mut spans)).style(Style::default().fg(color)));
+      line_width = 0;
+    }
+
+    if !spans.is_empty() {
+      spans.push(Span::from(" | "));
+      line_width += 3;
+    }
+
+    spans.push(label);
+    spans.push(description);
+    line_width += entry_width;
+  }
+
+  if !spans.is_empty() {
+    lines.push(Line::from(spans).style(Style::default().fg(color)));
+  }
+
+  lines
+}
+
+/// Returns a panel hint of the navigation keys, the bound panel actions and the close key.
+///
+/// A panel closes on the exit action or on its own toggle action.
+fn panel_hint(
+  keymap: &Keymap,
+  navigation: &str,
+  actions: &[Action],
+  toggle_action: Action,
+) -> Line<'static> {
+  let mut entries = vec![navigation.to_string()];
+
+  for action in actions {
+    if let Some(label) = keymap.label(*action) {
+      entries.push(format!("{} {}", label, action.description()));
+    }
+  }
+
+  if let Some(label) = keymap
+    .label(Action::Exit)
+    .or_else(|| keymap.label(toggle_action))
+  {
+    entries.push(format!("{} close", label));
+  }
+
+  Line::from(format!(" {} ", entries.join(" | "))).centered()
+}
+
+/// Finishes a GIF recording off the UI thread (the encoder flushes the queued frames)
+fn finish_gif_recording(gif_recorder: GifRecorder) {
+  tokio::task::spawn_blocking(move || drop(gif_recorder));
+}
+
+/// Returns a red recording indicator with the elapsed time
+fn recording_span(label: &str, elapsed: Duration) -> Span<'static> {
+  let elapsed = elapsed.as_secs();
+
+  Span::from(format!("● {} {:02}:{:02} ", label, elapsed / 60, elapsed % 60))
+    .style(Style::default().fg(Color::Red).bold())
 }
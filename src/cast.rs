@@ -0,0 +1,128 @@
+use std::{
+  fs::File,
+  io::{BufWriter, Write},
+  path::{Path, PathBuf},
+  time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use ratatui::text::Text;
+
+use crate::export;
+
+/// asciicast v2 recorder.
+///
+/// Streams rendered frames as output events, so the file stays valid if tuicam exits
+/// without stopping the recording.
+pub struct CastRecorder {
+  writer: BufWriter<File>,
+  path: PathBuf,
+  started_at: Instant,
+  size: (u16, u16),
+}
+
+impl CastRecorder {
+  /// Creates a cast file and writes the header
+  pub fn create(path: &Path, size: (u16, u16)) -> std::io::Result<Self> {
+    if let Some(dir) = path.parent() {
+      std::fs::create_dir_all(dir)?;
+    }
+
+    let mut writer = BufWriter::new(File::create(path)?);
+
+    let timestamp = SystemTime::now()
+      .duration_since(UNIX_EPOCH)
+      .map(|duration| duration.as_secs())
+      .unwrap_or(0);
+
+    writeln!(
+      writer,
+      "{{\"version\": 2, \"width\": {}, \"height\": {}, \"timestamp\": {}, \"title\": \"tuicam\"}}",
+      size.0, size.1, timestamp
+    )?;
+
+    Ok(Self {
+      writer,
+      path: path.to_path_buf(),
+      started_at: Instant::now(),
+      size,
+    })
+  }
+
+  /// Returns the recording duration
+  pub fn elapsed(&self) -> Duration {
+    self.started_at.elapsed()
+  }
+
+  /// Writes a resize event and clears the screen (nothing happens if the size is same)
+  fn resize(&mut self, width: u16, height: u16) -> std::io::Result<()> {
+    if self.size == (width, height) || width == 0 || height == 0 {
+      return Ok(());
+    }
+
+    self.size = (width, height);
+    self.write_event("r", &format!("{}x{}", width, height))?;
+    self.write_event("o", "\x1b[2J")
+  }
+
+  /// Writes a rendered frame (redrawn from the top left corner).
+  ///
+  /// A frame of another size resizes the recording.
+  pub fn write_frame(&mut self, text: &Text) -> std::io::Result<()> {
+    // Graphics protocol modes have no rendered characters
+    if text.lines.is_empty() {
+      return Ok(());
+    }
+
+    let (width, height) = frame_size(text);
+    self.resize(width, height)?;
+
+    let ansi = export::to_ansi(text);
+    let output = String::from("\x1b[H") + &ansi.trim_end_matches('\n').replace('\n', "\r\n");
+
+    self.write_event("o", &output)
+  }
+
+  /// Flushes the file and returns its path
+  pub fn finish(mut self) -> std::io::Result<PathBuf> {
+    self.writer.flush()?;
+    Ok(self.path)
+  }
+
+  /// Writes an event line (`[time, code, data]`)
+  fn write_event(&mut self, code: &str, data: &str) -> std::io::Result<()> {
+    writeln!(
+      self.writer,
+      "[{:.6}, \"{}\", {}]",
+      self.elapsed().as_secs_f64(),
+      code,
+      json_string(data)
+    )
+  }
+}
+
+/// Returns a size of the rendered frame in cells (columns, rows)
+pub fn frame_size(text: &Text) -> (u16, u16) {
+  let width = text.lines.iter().map(|line| line.width()).max().unwrap_or(0);
+  (width as u16, text.lines.len() as u16)
+}
+
+/// Returns a quoted and escaped JSON string
+fn json_string(value: &str) -> String {
+  let mut json = String::with_capacity(value.len() + 2);
+  json.push('"');
+
+  for c in value.chars() {
+    match c {
+      '"' => json.push_str("\\\""),
+      '\\' => json.push_str("\\\\"),
+      '\n' => json.push_str("\\n"),
+      '\r' => json.push_str("\\r"),
+      '\t' => json.push_str("\\t"),
+      c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+      c => json.push(c),
+    }
+  }
+
+  json.push('"');
+  json
+}
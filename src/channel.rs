@@ -1,14 +1,25 @@
 use ratatui::{crossterm::event::KeyEvent, text::Text};
 
+use crate::{error::TuicamError, graphics::GraphicsProtocol};
+
 pub enum AppEvent {
   // OpenCV mat (camera video frame)
   AsciiFrame(Text<'static>),
 
+  // Graphics protocol escape sequence (true-pixel camera video frame)
+  ImageFrame(GraphicsProtocol, Vec<u8>),
+
   // Crossterm KeyEvent
   Event(KeyEvent),
 
   // Crossterm Resize Event
   TerminalResize((u16, u16)),
+
+  // Short message shown in place of the help bar
+  Toast(String),
+
+  // Frame pipeline failure (shown in the camera block until a retry)
+  Error(TuicamError),
 }
 
 pub struct Channel {
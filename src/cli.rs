@@ -0,0 +1,635 @@
+use std::{collections::BTreeMap, path::PathBuf, time::Duration};
+
+use ratatui::style::Color;
+
+use crate::{
+  app::DEFAULT_BORDER_COLOR,
+  controls::CameraPreset,
+  dither::Dithering,
+  export::ExportFormat,
+  graphics::GraphicsProtocol,
+  handler::{CamWindowScale, CameraSelector, ImageConvertType},
+  keymap::Keymap,
+  pattern::TestPattern,
+  ramp::RampKind,
+  recorder::{RecordOptions, VideoCodec},
+  snapshot::SnapshotFormat,
+  source::DEFAULT_FRAME_INTERVAL,
+  threshold::{ThresholdMethod, MAX_BLOCK_SIZE, MIN_BLOCK_SIZE},
+};
+
+/// Default size of a headless frame in cells (columns, rows)
+const HEADLESS_DEFAULT_SIZE: (u16, u16) = (80, 24);
+
+/// Default number of frames skipped before a headless export
+const HEADLESS_DEFAULT_WARMUP: usize = 5;
+
+/// Maximal frame rate of a device camera or a test pattern
+pub const MAX_FPS: f64 = 120.0;
+
+const HELP: &str = "\
+Terminal-based camera with switchable modes
+
+Usage: tuicam [OPTIONS]
+
+Input:
+  --camera <INDEX|NAME|PATH>  Device camera index, (a part of) its name or bus, or a device
+                              path (e.g. /dev/v4l/by-id/...)
+  --input <FILE>              Video or image file used instead of a camera
+  --pattern <NAME>            Synthetic test pattern (bars, gradient, checkerboard, noise, ball)
+  --list-cameras              Lists available cameras with their formats and exits
+
+Capture:
+  --fps <FPS>                 Frame rate of a camera or a test pattern [default: 20]
+  --resolution <WxH>          Requested capture resolution of a camera (e.g. 640x480)
+                              [default: the smallest mode covering the camera window]
+  --fourcc <CODE>             Requested pixel format of a camera (e.g. YUYV, MJPG)
+  --mirror                    Mirrors the image horizontally
+
+View:
+  --mode <MODE>               Initial camera mode [default: halfblock]
+  --scale <small|full>        Initial camera window scale [default: small]
+  --ramp <CHARS>              Custom character ramp of the ASCII mode (lightest glyph first)
+  --threshold <VALUE>         Binary threshold of the threshold modes (0 - 255) [default: 128]
+  --threshold-method <NAME>   Threshold method (fixed, otsu, adaptive-mean, adaptive-gaussian)
+                              [default: fixed]
+  --block-size <PIXELS>       Neighbourhood of the adaptive threshold (odd, 3 - 99) [default: 15]
+  --dither <NAME>             Dithering of the threshold and Sixel modes (none, floyd-steinberg,
+                              atkinson, sierra, bayer2, bayer4, bayer8, blue-noise) [default: none]
+  --graphics <LIST>           Graphics protocols of the terminal, comma separated (kitty, sixel,
+                              iterm2) or none [default: detected]
+
+Settings:
+  --config <FILE>             Config file [default: ~/.config/tuicam/config.toml]
+
+Output:
+  --snapshot-dir <DIR>        Directory of snapshots, exports and recordings
+  --snapshot-format <FORMAT>  Snapshot image format (png, jpg) [default: png]
+  --codec <CODEC>             Video recording codec (mp4v, h264, mjpg, xvid) [default: mp4v]
+  --record-processed          Records the processed image instead of the raw camera frames
+
+Headless:
+  --headless                  Renders a single frame without the TUI and exits
+  --size <COLSxROWS>          Size of the headless frame in cells [default: 80x24]
+  --format <FORMAT>           Headless output format (ansi, txt, html, svg) [default: ansi]
+  --output <FILE>             Writes the headless frame into the file instead of stdout
+  --warmup <FRAMES>           Frames skipped before the headless frame [default: 5]
+
+  -h, --help                  Prints help
+  -V, --version               Prints version
+
+Modes: halfblock, colorful, grayscale, grayscale-threshold, threshold, ascii, braille,
+colorful-braille, sextant, octant, kitty, sixel, iterm2
+
+Environment:
+  TUICAM_GRAPHICS             Graphics protocols of the terminal (same as --graphics, which wins)
+  TUICAM_KITTY_SHM=1          Transfers Kitty frames by shared memory (local kitty on Linux only)
+";
+
+/// Startup options of the frame handler
+pub struct Options {
+  /// Requested device camera (the first one is used by default)
+  pub camera: Option<CameraSelector>,
+
+  /// Preferred device camera (ignored if not available)
+  pub preferred_camera: Option<CameraSelector>,
+
+  /// Video or image file used instead of a device camera
+  pub input: Option<PathBuf>,
+
+  /// Test pattern used instead of a device camera
+  pub pattern: Option<TestPattern>,
+
+  /// Initial camera mode
+  pub image_convert_type: ImageConvertType,
+
+  /// Initial camera window scale
+  pub cam_window_scale: CamWindowScale,
+
+  /// Graphics protocols of the terminal (detected if not given)
+  pub graphics_protocols: Option<Vec<GraphicsProtocol>>,
+
+  /// Delay between two frames of a device camera or a test pattern
+  pub frame_interval: Duration,
+
+  /// Requested capture resolution of a device camera (width, height)
+  pub resolution: Option<(i32, i32)>,
+
+  /// Requested pixel format of a device camera (FourCC)
+  pub fourcc: Option<String>,
+
+  /// Image is mirrored horizontally
+  pub is_mirrored: bool,
+
+  /// Binary threshold of the threshold modes
+  pub threshold: u8,
+
+  /// Threshold method of the threshold modes
+  pub threshold_method: ThresholdMethod,
+
+  /// Neighbourhood size of the adaptive threshold in pixels
+  pub block_size: i32,
+
+  /// Dithering of the threshold modes and the Sixel palette
+  pub dithering: Dithering,
+
+  /// Initial character ramp of the ASCII art mode
+  pub ramp_kind: RampKind,
+
+  /// Custom character ramp of the ASCII art mode
+  pub custom_ramp: Option<String>,
+
+  /// Border color of the camera block and the help bar
+  pub border_color: Color,
+
+  /// Key bindings of the user actions
+  pub keymap: Keymap,
+
+  /// Camera control presets by the camera selector
+  pub camera_presets: BTreeMap<String, CameraPreset>,
+
+  /// Directory of saved snapshots
+  pub snapshot_dir: Option<PathBuf>,
+
+  /// Image format of saved snapshots
+  pub snapshot_format: SnapshotFormat,
+
+  /// Video recording options
+  pub record_options: RecordOptions,
+}
+
+impl Default for Options {
+  fn default() -> Self {
+    Self {
+      camera: None,
+      preferred_camera: None,
+      input: None,
+      pattern: None,
+      image_convert_type: ImageConvertType::ColorfulHalfBlock,
+      cam_window_scale: CamWindowScale::Small,
+      graphics_protocols: None,
+      frame_interval: DEFAULT_FRAME_INTERVAL,
+      resolution: None,
+      fourcc: None,
+      is_mirrored: false,
+      threshold: 128,
+      threshold_method: ThresholdMethod::Fixed,
+      block_size: 15,
+      dithering: Dithering::None,
+      ramp_kind: RampKind::Classic,
+      custom_ramp: None,
+      border_color: DEFAULT_BORDER_COLOR,
+      keymap: Keymap::default(),
+      camera_presets: BTreeMap::new(),
+      snapshot_dir: None,
+      snapshot_format: SnapshotFormat::Png,
+      record_options: RecordOptions {
+        codec: VideoCodec::Mp4v,
+        is_processed: false,
+      },
+    }
+  }
+}
+
+/// Options of a headless export (a single frame rendered without the TUI)
+pub struct HeadlessOptions {
+  /// Frame size in cells (columns, rows)
+  pub size: (u16, u16),
+
+  /// Output format
+  pub format: ExportFormat,
+
+  /// Output file (stdout if not given)
+  pub output: Option<PathBuf>,
+
+  /// Number of frames skipped before the exported one (lets a camera adjust its exposure)
+  pub warmup: usize,
+}
+
+/// Command given on the command line
+pub enum Command {
+  /// Runs the TUI
+  Run(Options),
+
+  /// Renders a single frame without the TUI
+  Headless(Options, HeadlessOptions),
+
+  /// Lists available cameras
+  ListCameras,
+
+  /// Prints help
+  Help,
+
+  /// Prints version
+  Version,
+}
+
+impl Command {
+  /// Parses command line arguments (without the program name).
+  ///
+  /// The options override the given ones (seeded by the config file).
+  pub fn parse(
+    args: impl IntoIterator<Item = String>,
+    mut options: Options,
+  ) -> Result<Self, String> {
+    let mut headless = HeadlessOptions {
+      size: HEADLESS_DEFAULT_SIZE,
+      format: ExportFormat::Ansi,
+      output: None,
+      warmup: HEADLESS_DEFAULT_WARMUP,
+    };
+
+    let mut is_headless = false;
+    let mut is_list_cameras = false;
+    let mut is_mode_given = false;
+
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+      match arg.as_str() {
+        "-h" | "--help" => return Ok(Command::Help),
+        "-V" | "--version" => return Ok(Command::Version),
+        "--list-cameras" => is_list_cameras = true,
+        // Loaded before the parsing, see `config_path`
+        "--config" => {
+          value(&mut args, &arg)?;
+        }
+        "--camera" => options.camera = Some(CameraSelector::parse(&value(&mut args, &arg)?)),
+        "--input" => options.input = Some(PathBuf::from(value(&mut args, &arg)?)),
+        "--pattern" => {
+          let name = value(&mut args, &arg)?;
+          options.pattern = Some(TestPattern::from_name(&name).ok_or_else(|| {
+            format!(
+              "Unknown test pattern {} (available: {})",
+              name,
+              TestPattern::ALL.map(|pattern| pattern.name()).join(", ")
+            )
+          })?);
+        }
+        "--fps" => {
+          let fps = value(&mut args, &arg)?;
+          let fps = fps
+            .parse::<f64>()
+            .ok()
+            .filter(|fps| *fps > 0.0 && *fps <= MAX_FPS)
+            .ok_or_else(|| format!("Invalid frame rate {} (expected 0 - {})", fps, MAX_FPS))?;
+
+          options.frame_interval = Duration::from_secs_f64(1.0 / fps);
+        }
+        "--resolution" => {
+          let resolution = value(&mut args, &arg)?;
+          let resolution = parse_resolution(&resolution)
+            .ok_or_else(|| format!("Invalid resolution {} (expected WIDTHxHEIGHT)", resolution))?;
+
+          options.resolution = Some(resolution);
+        }
+        "--fourcc" => options.fourcc = Some(parse_fourcc(&value(&mut args, &arg)?)?),
+        "--mirror" => options.is_mirrored = true,
+        "--mode" => {
+          is_mode_given = true;
+
+          let name = value(&mut args, &arg)?;
+          options.image_convert_type = ImageConvertType::from_name(&name).ok_or_else(|| {
+            format!(
+              "Unknown mode {} (available: {})",
+              name,
+              ImageConvertType::ALL.map(|mode| mode.name()).join(", ")
+            )
+          })?;
+        }
+        "--scale" => {
+          let name = value(&mut args, &arg)?;
+          options.cam_window_scale = CamWindowScale::from_name(&name)
+            .ok_or_else(|| format!("Unknown scale {} (available: small, full)", name))?;
+        }
+        "--graphics" => {
+          options.graphics_protocols =
+            Some(GraphicsProtocol::parse_list(&value(&mut args, &arg)?)?);
+        }
+        "--ramp" => options.custom_ramp = Some(value(&mut args, &arg)?),
+        "--threshold" => {
+          let threshold = value(&mut args, &arg)?;
+          options.threshold = threshold
+            .parse::<u8>()
+            .map_err(|_| format!("Invalid threshold {} (expected 0 - 255)", threshold))?;
+        }
+        "--threshold-method" => {
+          let name = value(&mut args, &arg)?;
+          options.threshold_method = ThresholdMethod::from_name(&name).ok_or_else(|| {
+            format!(
+              "Unknown threshold method {} (available: {})",
+              name,
+              ThresholdMethod::ALL.map(|method| method.name()).join(", ")
+            )
+          })?;
+        }
+        "--block-size" => options.block_size = parse_block_size(&value(&mut args, &arg)?)?,
+        "--dither" => {
+          let name = value(&mut args, &arg)?;
+          options.dithering = Dithering::from_name(&name).ok_or_else(|| {
+            format!(
+              "Unknown dithering {} (available: {})",
+              name,
+              Dithering::ALL.map(|dithering| dithering.name()).join(", ")
+            )
+          })?;
+        }
+        "--snapshot-dir" => options.snapshot_dir = Some(PathBuf::from(value(&mut args, &arg)?)),
+        "--snapshot-format" => {
+          let name = value(&mut args, &arg)?;
+          options.snapshot_format = SnapshotFormat::from_name(&name)
+            .ok_or_else(|| format!("Unknown snapshot format {} (available: png, jpg)", name))?;
+        }
+        "--codec" => {
+          let name = value(&mut args, &arg)?;
+          options.record_options.codec = VideoCodec::from_name(&name).ok_or_else(|| {
+            format!(
+              "Unknown video codec {} (available: {})",
+              name,
+              VideoCodec::ALL.map(|codec| codec.name()).join(", ")
+            )
+          })?;
+        }
+        "--record-processed" => options.record_options.is_processed = true,
+        "--headless" => is_headless = true,
+        "--size" => {
+          let size = value(&mut args, &arg)?;
+          let (cols, rows) = parse_size(&size)
+            .filter(|(cols, rows)| *cols <= u16::MAX as u32 && *rows <= u16::MAX as u32)
+            .ok_or_else(|| format!("Invalid size {} (expected COLSxROWS)", size))?;
+
+          headless.size = (cols as u16, rows as u16);
+        }
+        "--format" => {
+          let name = value(&mut args, &arg)?;
+          headless.format = ExportFormat::from_name(&name).ok_or_else(|| {
+            format!(
+              "Unknown output format {} (available: {})",
+              name,
+              ExportFormat::ALL.map(|format| format.name()).join(", ")
+            )
+          })?;
+        }
+        "--output" => headless.output = Some(PathBuf::from(value(&mut args, &arg)?)),
+        "--warmup" => {
+          let warmup = value(&mut args, &arg)?;
+          headless.warmup = warmup
+            .parse()
+            .map_err(|_| format!("Invalid number of warmup frames {}", warmup))?;
+        }
+        _ => return Err(format!("Unknown option {} (see --help)", arg)),
+      }
+    }
+
+    if is_list_cameras {
+      return Ok(Command::ListCameras);
+    }
+
+    if is_headless && options.image_convert_type.graphics_protocol().is_some() {
+      if is_mode_given {
+        return Err(format!(
+          "Mode {} can't be rendered in the headless mode",
+          options.image_convert_type.name()
+        ));
+      }
+
+      // A graphics mode of the config file falls back to a text mode
+      options.image_convert_type = ImageConvertType::ColorfulHalfBlock;
+    }
+
+    if is_headless {
+      return Ok(Command::Headless(options, headless));
+    }
+
+    Ok(Command::Run(options))
+  }
+}
+
+/// Prints help
+pub fn print_help() {
+  print!("{}", HELP);
+}
+
+/// Prints version
+pub fn print_version() {
+  println!("tuicam {}", env!("CARGO_PKG_VERSION"));
+}
+
+/// Returns a config file path given on the command line
+pub fn config_path(args: &[String]) -> Option<PathBuf> {
+  args
+    .iter()
+    .skip_while(|arg| *arg != "--config")
+    .nth(1)
+    .map(PathBuf::from)
+}
+
+/// Returns a value of the command line option
+fn value(args: &mut impl Iterator<Item = String>, name: &str) -> Result<String, String> {
+  args
+    .next()
+    .ok_or_else(|| format!("Missing value of the {} option", name))
+}
+
+/// Parses a pixel format code (4 characters)
+pub fn parse_fourcc(fourcc: &str) -> Result<String, String> {
+  if fourcc.chars().count() != 4 || !fourcc.is_ascii() {
+    return Err(format!(
+      "Invalid pixel format {} (expected 4 characters)",
+      fourcc
+    ));
+  }
+
+  Ok(fourcc.to_string())
+}
+
+/// Parses a block size of the adaptive threshold (odd, OpenCV rejects even sizes)
+pub fn parse_block_size(block_size: &str) -> Result<i32, String> {
+  block_size
+    .trim()
+    .parse::<i32>()
+    .ok()
+    .filter(|size| (MIN_BLOCK_SIZE..=MAX_BLOCK_SIZE).contains(size) && size % 2 == 1)
+    .ok_or_else(|| {
+      format!(
+        "Invalid block size {} (expected an odd number {} - {})",
+        block_size, MIN_BLOCK_SIZE, MAX_BLOCK_SIZE
+      )
+    })
+}
+
+/// Parses a capture resolution given as `WIDTHxHEIGHT` (OpenCV takes `i32` sizes)
+pub fn parse_resolution(resolution: &str) -> Option<(i32, i32)> {
+  parse_size(resolution)
+    .filter(|(width, height)| *width <= i32::MAX as u32 && *height <= i32::MAX as u32)
+    .map(|(width, height)| (width as i32, height as i32))
+}
+
+/// Parses a size given as `WIDTHxHEIGHT` (both non-zero)
+pub fn parse_size(size: &str) -> Option<(u32, u32)> {
+  let (width, height) = size
+    .to_lowercase()
+    .split_once('x')
+    .map(|(width, height)| (width.trim().parse::<u32>(), height.trim().parse::<u32>()))?;
+
+  match (width, height) {
+    (Ok(width), Ok(height)) if width > 0 && height > 0 => Some((width, height)),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(args: &[&str]) -> Result<Command, String> {
+    parse_with(args, Options::default())
+  }
+
+  fn parse_with(args: &[&str], options: Options) -> Result<Command, String> {
+    Command::parse(args.iter().map(|arg| arg.to_string()), options)
+  }
+
+  #[test]
+  fn parse_valid_size() {
+    assert_eq!(parse_size("80x24"), Some((80, 24)));
+    assert_eq!(parse_size("1920X1080"), Some((1920, 1080)));
+    assert_eq!(parse_size(" 640 x 480 "), Some((640, 480)));
+  }
+
+  #[test]
+  fn parse_invalid_size() {
+    assert_eq!(parse_size("80"), None);
+    assert_eq!(parse_size("0x24"), None);
+    assert_eq!(parse_size("80x0"), None);
+    assert_eq!(parse_size("-80x24"), None);
+    assert_eq!(parse_size("80x24x1"), None);
+    assert_eq!(parse_size("4294967296x1"), None);
+  }
+
+  #[test]
+  fn parse_resolution_within_opencv_size() {
+    assert_eq!(parse_resolution("1280x720"), Some((1280, 720)));
+    assert_eq!(parse_resolution("2147483647x1"), Some((i32::MAX, 1)));
+    assert_eq!(parse_resolution("2147483648x1"), None);
+    assert_eq!(parse_resolution("1x4294967295"), None);
+  }
+
+  #[test]
+  fn parse_run_options() {
+    let Ok(Command::Run(options)) = parse(&[
+      "--mode",
+      "braille",
+      "--scale",
+      "full",
+      "--resolution",
+      "1280x720",
+      "--fps",
+      "4",
+      "--mirror",
+    ]) else {
+      panic!("expected the run command");
+    };
+
+    assert!(options.image_convert_type == ImageConvertType::Braille);
+    assert!(options.cam_window_scale == CamWindowScale::Full);
+    assert_eq!(options.resolution, Some((1280, 720)));
+    assert_eq!(options.frame_interval, Duration::from_millis(250));
+    assert!(options.is_mirrored);
+  }
+
+  #[test]
+  fn parse_headless_options() {
+    let Ok(Command::Headless(_, headless)) = parse(&[
+      "--headless",
+      "--size",
+      "120x40",
+      "--format",
+      "html",
+      "--warmup",
+      "0",
+    ]) else {
+      panic!("expected the headless command");
+    };
+
+    assert_eq!(headless.size, (120, 40));
+    assert_eq!(headless.format, ExportFormat::Html);
+    assert_eq!(headless.warmup, 0);
+  }
+
+  #[test]
+  fn parse_graphics_protocols() {
+    let Ok(Command::Run(options)) = parse(&["--graphics", "sixel, kitty"]) else {
+      panic!("expected the run command");
+    };
+
+    assert_eq!(
+      options.graphics_protocols,
+      Some(vec![GraphicsProtocol::Sixel, GraphicsProtocol::Kitty])
+    );
+
+    let Ok(Command::Run(options)) = parse(&["--graphics", "none"]) else {
+      panic!("expected the run command");
+    };
+
+    assert_eq!(options.graphics_protocols, Some(Vec::new()));
+  }
+
+  #[test]
+  fn parse_commands_before_errors() {
+    assert!(matches!(parse(&["--mode", "ascii", "--help"]), Ok(Command::Help)));
+    assert!(matches!(parse(&["-V"]), Ok(Command::Version)));
+    assert!(matches!(
+      parse(&["--list-cameras", "--headless"]),
+      Ok(Command::ListCameras)
+    ));
+  }
+
+  #[test]
+  fn reject_invalid_options() {
+    assert_eq!(
+      parse(&["--fly"]).err(),
+      Some(String::from("Unknown option --fly (see --help)"))
+    );
+    assert_eq!(
+      parse(&["--camera"]).err(),
+      Some(String::from("Missing value of the --camera option"))
+    );
+    assert_eq!(
+      parse(&["--resolution", "2147483648x720"]).err(),
+      Some(String::from(
+        "Invalid resolution 2147483648x720 (expected WIDTHxHEIGHT)"
+      ))
+    );
+    assert_eq!(
+      parse(&["--headless", "--size", "65536x24"]).err(),
+      Some(String::from("Invalid size 65536x24 (expected COLSxROWS)"))
+    );
+    assert_eq!(
+      parse(&["--fps", "0"]).err(),
+      Some(String::from("Invalid frame rate 0 (expected 0 - 120)"))
+    );
+  }
+
+  #[test]
+  fn reject_headless_graphics_mode() {
+    assert_eq!(
+      parse(&["--headless", "--mode", "kitty"]).err(),
+      Some(String::from("Mode kitty can't be rendered in the headless mode"))
+    );
+  }
+
+  #[test]
+  fn headless_falls_back_from_config_graphics_mode() {
+    let options = Options {
+      image_convert_type: ImageConvertType::Sixel,
+      ..Options::default()
+    };
+
+    let Ok(Command::Headless(options, _)) = parse_with(&["--headless"], options) else {
+      panic!("expected the headless command");
+    };
+
+    assert!(options.image_convert_type == ImageConvertType::ColorfulHalfBlock);
+  }
+}
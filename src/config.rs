@@ -0,0 +1,361 @@
+use std::{
+  collections::BTreeMap,
+  path::{Path, PathBuf},
+  time::Duration,
+};
+
+use ratatui::style::Color;
+use serde::{Deserialize, Serialize};
+use toml_edit::DocumentMut;
+
+use crate::{
+  cli::{self, Options, MAX_FPS},
+  controls::{self, CameraPreset},
+  dither::Dithering,
+  graphics::GraphicsProtocol,
+  handler::{CamWindowScale, CameraSelector, FrameHandlerConfig, ImageConvertType},
+  keymap::{KeyBindings, Keymap},
+  ramp::RampKind,
+  threshold::ThresholdMethod,
+};
+
+/// Config file path inside of the user config directory
+const CONFIG_FILE_PATH: &str = "tuicam/config.toml";
+
+/// Keys of the last used settings (written back with `save-on-exit`)
+const LAST_USED_KEYS: &[&str] = &[
+  "mode",
+  "scale",
+  "camera",
+  "ramp",
+  "custom-ramp",
+  "threshold",
+  "threshold-method",
+  "block-size",
+  "dither",
+];
+
+/// Persistent settings (`~/.config/tuicam/config.toml`).
+///
+/// Every value is optional and the command line options take precedence.
+/// With `save-on-exit` the last used mode, scale, camera, ramp, threshold and dithering are written back
+/// (graphics modes are not saved).
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct ConfigFile {
+  /// Default camera mode (`halfblock`, `ascii`, ...)
+  pub mode: Option<String>,
+
+  /// Default camera window scale (`small` or `full`)
+  pub scale: Option<String>,
+
+  /// Graphics protocols of the terminal (`kitty,sixel` or `none`, detected if not set)
+  pub graphics: Option<String>,
+
+  /// Preferred camera index, device path or (a part of) its name
+  pub camera: Option<String>,
+
+  /// Frame rate of a device camera or a test pattern
+  pub fps: Option<f64>,
+
+  /// Capture resolution of a device camera (`640x480`, picked automatically if not set)
+  pub resolution: Option<String>,
+
+  /// Pixel format of a device camera (`YUYV`, `MJPG`, ...)
+  pub fourcc: Option<String>,
+
+  /// Binary threshold of the threshold modes (0 - 255)
+  pub threshold: Option<u8>,
+
+  /// Threshold method (`fixed`, `otsu`, `adaptive-mean` or `adaptive-gaussian`)
+  pub threshold_method: Option<String>,
+
+  /// Neighbourhood of the adaptive threshold in pixels (odd, 3 - 99)
+  pub block_size: Option<i32>,
+
+  /// Dithering of the threshold and Sixel modes (`none`, `floyd-steinberg`, `bayer4`, ...)
+  pub dither: Option<String>,
+
+  /// Default character ramp of the ASCII mode (`classic`, `bourke`, `blocks` or `custom`)
+  pub ramp: Option<String>,
+
+  /// Custom character ramp of the ASCII mode (lightest glyph first)
+  pub custom_ramp: Option<String>,
+
+  /// Border color (a color name or `#rrggbb`)
+  pub border_color: Option<String>,
+
+  /// Directory of snapshots, exports and recordings
+  pub snapshot_dir: Option<PathBuf>,
+
+  /// Saves the last used settings on exit
+  pub save_on_exit: bool,
+
+  /// Key sequences of the actions (`snapshot = "s"`, `exit = ["q", "esc"]`)
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub keys: BTreeMap<String, KeyBindings>,
+
+  /// Camera control presets by the camera (`[presets."/dev/v4l/by-id/..."]`, `brightness = 128`)
+  #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+  pub presets: BTreeMap<String, BTreeMap<String, f64>>,
+
+  /// Path of the loaded file
+  #[serde(skip)]
+  path: Option<PathBuf>,
+}
+
+impl ConfigFile {
+  /// Loads the config file.
+  ///
+  /// A missing default file gives an empty config, a missing given file is an error.
+  pub fn load(path: Option<PathBuf>) -> Result<Self, String> {
+    let is_default_path = path.is_none();
+
+    let Some(path) = path.or_else(default_path) else {
+      return Ok(Self::default());
+    };
+
+    let content = match std::fs::read_to_string(&path) {
+      Ok(content) => content,
+      Err(err) if is_default_path && err.kind() == std::io::ErrorKind::NotFound => {
+        return Ok(Self {
+          path: Some(path),
+          ..Self::default()
+        });
+      }
+      Err(err) => {
+        return Err(format!(
+          "Unable to read config file {}: {}",
+          path.display(),
+          err
+        ))
+      }
+    };
+
+    let mut config_file = toml::from_str::<Self>(&content)
+      .map_err(|err| format!("Invalid config file {}: {}", path.display(), err))?;
+
+    config_file.path = Some(path);
+
+    Ok(config_file)
+  }
+
+  /// Returns startup options seeded by the config values
+  pub fn options(&self) -> Result<Options, String> {
+    let mut options = Options::default();
+
+    self.apply(&mut options).map_err(|err| {
+      let path = self.path.as_deref().unwrap_or(Path::new(CONFIG_FILE_PATH));
+      format!("Invalid config file {}: {}", path.display(), err)
+    })?;
+
+    Ok(options)
+  }
+
+  /// Sets the options of the config values
+  fn apply(&self, options: &mut Options) -> Result<(), String> {
+    if let Some(mode) = self.mode.as_deref() {
+      options.image_convert_type =
+        ImageConvertType::from_name(mode).ok_or_else(|| format!("unknown mode {}", mode))?;
+    }
+
+    if let Some(scale) = self.scale.as_deref() {
+      options.cam_window_scale =
+        CamWindowScale::from_name(scale).ok_or_else(|| format!("unknown scale {}", scale))?;
+    }
+
+    if let Some(graphics) = self.graphics.as_deref() {
+      options.graphics_protocols = Some(GraphicsProtocol::parse_list(graphics)?);
+    }
+
+    if let Some(camera) = self.camera.as_deref() {
+      options.preferred_camera = Some(CameraSelector::parse(camera));
+    }
+
+    if let Some(fps) = self.fps {
+      if fps <= 0.0 || fps > MAX_FPS {
+        return Err(format!("invalid fps {} (expected 0 - {})", fps, MAX_FPS));
+      }
+
+      options.frame_interval = Duration::from_secs_f64(1.0 / fps);
+    }
+
+    if let Some(resolution) = self.resolution.as_deref() {
+      options.resolution = Some(
+        cli::parse_resolution(resolution)
+          .ok_or_else(|| format!("invalid resolution {} (expected WIDTHxHEIGHT)", resolution))?,
+      );
+    }
+
+    if let Some(fourcc) = self.fourcc.as_deref() {
+      options.fourcc = Some(cli::parse_fourcc(fourcc)?);
+    }
+
+    if let Some(threshold) = self.threshold {
+      options.threshold = threshold;
+    }
+
+    if let Some(threshold_method) = self.threshold_method.as_deref() {
+      options.threshold_method = ThresholdMethod::from_name(threshold_method)
+        .ok_or_else(|| format!("unknown threshold method {}", threshold_method))?;
+    }
+
+    if let Some(block_size) = self.block_size {
+      options.block_size = cli::parse_block_size(&block_size.to_string())?;
+    }
+
+    if let Some(dither) = self.dither.as_deref() {
+      options.dithering =
+        Dithering::from_name(dither).ok_or_else(|| format!("unknown dithering {}", dither))?;
+    }
+
+    if let Some(custom_ramp) = self.custom_ramp.as_ref() {
+      options.custom_ramp = Some(custom_ramp.clone());
+    }
+
+    if let Some(ramp) = self.ramp.as_deref() {
+      options.ramp_kind =
+        RampKind::from_name(ramp).ok_or_else(|| format!("unknown ramp {}", ramp))?;
+
+      if options.ramp_kind == RampKind::Custom && options.custom_ramp.is_none() {
+        return Err(String::from(
+          "custom ramp is selected, but custom-ramp is not set",
+        ));
+      }
+    }
+
+    if let Some(border_color) = self.border_color.as_deref() {
+      options.border_color = border_color
+        .parse::<Color>()
+        .map_err(|_| format!("invalid border color {}", border_color))?;
+    }
+
+    if let Some(snapshot_dir) = self.snapshot_dir.as_ref() {
+      options.snapshot_dir = Some(expand_home(snapshot_dir));
+    }
+
+    options.keymap = Keymap::new(&self.keys)?;
+
+    for (camera, preset) in self.presets.iter() {
+      let preset = controls::parse_preset(preset)
+        .map_err(|err| format!("{} in the preset of {}", err, camera))?;
+
+      options.camera_presets.insert(camera.clone(), preset);
+    }
+
+    Ok(())
+  }
+
+  /// Takes over the last used settings
+  pub fn update(&mut self, config: &FrameHandlerConfig) {
+    // A graphics mode may be unsupported by the next terminal (and by the headless export)
+    if config.image_convert_type.graphics_protocol().is_none() {
+      self.mode = Some(config.image_convert_type.name().to_string());
+    }
+
+    self.scale = Some(config.cam_window_scale.name().to_string());
+    self.ramp = Some(config.ascii_ramp.kind().name().to_string());
+    self.threshold = Some(config.threshold.value());
+    self.threshold_method = Some(config.threshold.method().name().to_string());
+    self.block_size = Some(config.threshold.block_size());
+    self.dither = Some(config.dithering.name().to_string());
+
+    // The custom ramp may come from the command line only, the saved ramp must load again
+    if config.ascii_ramp.kind() == RampKind::Custom {
+      self.custom_ramp = config.ascii_ramp.custom().map(String::from);
+    }
+
+    // A persistent link or a name survives reboots which reshuffle the indices
+    if let Some(device) = config.camera.active_device() {
+      self.camera = Some(CameraSelector::stable(device).to_string());
+    }
+  }
+
+  /// Takes over the camera control presets and writes the config file
+  pub fn save_presets(
+    &mut self,
+    presets: &BTreeMap<String, CameraPreset>,
+  ) -> Result<&Path, String> {
+    self.presets = presets
+      .iter()
+      .map(|(camera, preset)| {
+        let preset = preset
+          .iter()
+          .map(|(control, value)| (control.name().to_string(), *value))
+          .collect();
+
+        (camera.clone(), preset)
+      })
+      .collect();
+
+    self.save(&["presets"])
+  }
+
+  /// Writes the config file (if the last used settings should be saved)
+  pub fn save_on_exit(&self) -> Result<(), String> {
+    if !self.save_on_exit {
+      return Ok(());
+    }
+
+    self.save(LAST_USED_KEYS).map(|_| ())
+  }
+
+  /// Writes the keys into the config file, returns its path.
+  ///
+  /// Only the keys are replaced, other values, comments and the ordering of the file are kept.
+  fn save(&self, keys: &[&str]) -> Result<&Path, String> {
+    let Some(path) = self.path.as_ref() else {
+      return Err(String::from("Unable to find the config directory"));
+    };
+
+    // Serialized by serde, so the keys are formatted as a new file
+    let values = toml::to_string(self)
+      .map_err(|err| format!("Unable to serialize config file: {}", err))?
+      .parse::<DocumentMut>()
+      .map_err(|err| format!("Unable to serialize config file: {}", err))?;
+
+    // The file is read again, it may be edited while the app runs
+    let mut document = match std::fs::read_to_string(path) {
+      Ok(content) => content
+        .parse::<DocumentMut>()
+        .map_err(|err| format!("Invalid config file {}: {}", path.display(), err))?,
+      Err(err) if err.kind() == std::io::ErrorKind::NotFound => DocumentMut::new(),
+      Err(err) => {
+        return Err(format!(
+          "Unable to read config file {}: {}",
+          path.display(),
+          err
+        ))
+      }
+    };
+
+    for key in keys {
+      if let Some(value) = values.get(key) {
+        document[*key] = value.clone();
+      }
+    }
+
+    if let Some(dir) = path.parent() {
+      std::fs::create_dir_all(dir)
+        .map_err(|err| format!("Unable to create {}: {}", dir.display(), err))?;
+    }
+
+    std::fs::write(path, document.to_string())
+      .map_err(|err| format!("Unable to write config file {}: {}", path.display(), err))?;
+
+    Ok(path)
+  }
+}
+
+/// Returns a default config file path (`$XDG_CONFIG_HOME/tuicam/config.toml`)
+pub fn default_path() -> Option<PathBuf> {
+  dirs::config_dir().map(|config_dir| config_dir.join(CONFIG_FILE_PATH))
+}
+
+/// Expands a leading `~` into the home directory
+fn expand_home(path: &Path) -> PathBuf {
+  match (path.strip_prefix("~"), dirs::home_dir()) {
+    (Ok(path), Some(home_dir)) => home_dir.join(path),
+    _ => path.to_path_buf(),
+  }
+}
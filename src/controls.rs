@@ -0,0 +1,257 @@
+use std::collections::BTreeMap;
+
+use opencv::{
+  prelude::*,
+  videoio::{self, VideoCapture},
+};
+
+/// Camera control values of a preset (applied in the control order)
+pub type CameraPreset = BTreeMap<CameraControl, f64>;
+
+/// Camera control (an OpenCV capture property).
+///
+/// Automatic modes go before the values they lock, so a preset switches
+/// the automatic mode off before it sets the value.
+#[derive(Eq, PartialEq, Ord, PartialOrd, Clone, Copy, Debug)]
+pub enum CameraControl {
+  Brightness,
+  Contrast,
+  Saturation,
+  AutoExposure,
+  Exposure,
+  Gain,
+  AutoWhiteBalance,
+  WhiteBalance,
+  Autofocus,
+  Focus,
+  Zoom,
+}
+
+impl CameraControl {
+  /// All controls in the panel order
+  pub const ALL: [CameraControl; 11] = [
+    CameraControl::Brightness,
+    CameraControl::Contrast,
+    CameraControl::Saturation,
+    CameraControl::AutoExposure,
+    CameraControl::Exposure,
+    CameraControl::Gain,
+    CameraControl::AutoWhiteBalance,
+    CameraControl::WhiteBalance,
+    CameraControl::Autofocus,
+    CameraControl::Focus,
+    CameraControl::Zoom,
+  ];
+
+  /// Returns a control name (used by the config file presets)
+  pub fn name(&self) -> &'static str {
+    match self {
+      CameraControl::Brightness => "brightness",
+      CameraControl::Contrast => "contrast",
+      CameraControl::Saturation => "saturation",
+      CameraControl::AutoExposure => "auto-exposure",
+      CameraControl::Exposure => "exposure",
+      CameraControl::Gain => "gain",
+      CameraControl::AutoWhiteBalance => "auto-white-balance",
+      CameraControl::WhiteBalance => "white-balance",
+      CameraControl::Autofocus => "autofocus",
+      CameraControl::Focus => "focus",
+      CameraControl::Zoom => "zoom",
+    }
+  }
+
+  /// Finds a control by the name
+  pub fn from_name(name: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|control| control.name() == name)
+  }
+
+  /// Returns a label (shown in the controls panel)
+  pub fn label(&self) -> &'static str {
+    match self {
+      CameraControl::Brightness => "Brightness",
+      CameraControl::Contrast => "Contrast",
+      CameraControl::Saturation => "Saturation",
+      CameraControl::AutoExposure => "Auto exposure",
+      CameraControl::Exposure => "Exposure",
+      CameraControl::Gain => "Gain",
+      CameraControl::AutoWhiteBalance => "Auto white balance",
+      CameraControl::WhiteBalance => "White balance",
+      CameraControl::Autofocus => "Autofocus",
+      CameraControl::Focus => "Focus",
+      CameraControl::Zoom => "Zoom",
+    }
+  }
+
+  /// Returns an OpenCV capture property of the control
+  pub fn property(&self) -> i32 {
+    match self {
+      CameraControl::Brightness => videoio::CAP_PROP_BRIGHTNESS,
+      CameraControl::Contrast => videoio::CAP_PROP_CONTRAST,
+      CameraControl::Saturation => videoio::CAP_PROP_SATURATION,
+      CameraControl::AutoExposure => videoio::CAP_PROP_AUTO_EXPOSURE,
+      CameraControl::Exposure => videoio::CAP_PROP_EXPOSURE,
+      CameraControl::Gain => videoio::CAP_PROP_GAIN,
+      CameraControl::AutoWhiteBalance => videoio::CAP_PROP_AUTO_WB,
+      CameraControl::WhiteBalance => videoio::CAP_PROP_WB_TEMPERATURE,
+      CameraControl::Autofocus => videoio::CAP_PROP_AUTOFOCUS,
+      CameraControl::Focus => videoio::CAP_PROP_FOCUS,
+      CameraControl::Zoom => videoio::CAP_PROP_ZOOM,
+    }
+  }
+
+  /// Returns V4L2 control id of the control (the V4L2 backend passes raw values)
+  pub fn v4l2_id(&self) -> u32 {
+    match self {
+      CameraControl::Brightness => 0x0098_0900,
+      CameraControl::Contrast => 0x0098_0901,
+      CameraControl::Saturation => 0x0098_0902,
+      CameraControl::AutoExposure => 0x009a_0901,
+      CameraControl::Exposure => 0x009a_0902,
+      CameraControl::Gain => 0x0098_0913,
+      CameraControl::AutoWhiteBalance => 0x0098_090c,
+      CameraControl::WhiteBalance => 0x0098_091a,
+      CameraControl::Autofocus => 0x009a_090c,
+      CameraControl::Focus => 0x009a_090a,
+      CameraControl::Zoom => 0x009a_090d,
+    }
+  }
+
+  /// Returns values of an automatic mode switch (off, on), None for other controls.
+  ///
+  /// V4L2 auto exposure is a menu (1 manual, 3 aperture priority),
+  /// other backends use 0.25 and 0.75.
+  pub fn switch_values(&self) -> Option<(f64, f64)> {
+    match self {
+      CameraControl::AutoExposure if cfg!(target_os = "linux") => Some((1.0, 3.0)),
+      CameraControl::AutoExposure => Some((0.25, 0.75)),
+      CameraControl::AutoWhiteBalance | CameraControl::Autofocus => Some((0.0, 1.0)),
+      _ => None,
+    }
+  }
+
+  /// Returns an automatic mode which locks the control value (if it's on)
+  pub fn auto_control(&self) -> Option<CameraControl> {
+    match self {
+      CameraControl::Exposure => Some(CameraControl::AutoExposure),
+      CameraControl::WhiteBalance => Some(CameraControl::AutoWhiteBalance),
+      CameraControl::Focus => Some(CameraControl::Autofocus),
+      _ => None,
+    }
+  }
+}
+
+/// Value range of a control reported by the camera
+#[derive(Clone, Copy, Debug)]
+pub struct ControlRange {
+  pub min: f64,
+  pub max: f64,
+  pub step: f64,
+  pub default: f64,
+}
+
+impl ControlRange {
+  /// Returns a position of the value in the range (0.0 - 1.0)
+  pub fn position(&self, value: f64) -> f64 {
+    if self.max > self.min {
+      ((value - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    } else {
+      0.0
+    }
+  }
+}
+
+/// Camera control values shared by the app and the frame source.
+///
+/// The app requests changes, the frame source applies them to the active
+/// capture and reads the values back.
+#[derive(Default)]
+pub struct CameraControls {
+  /// Values read from the camera (unsupported controls are missing)
+  values: BTreeMap<CameraControl, f64>,
+
+  /// Changes waiting for the frame source
+  pending: Vec<(CameraControl, f64)>,
+}
+
+impl CameraControls {
+  /// Returns a current value of the control
+  pub fn value(&self, control: CameraControl) -> Option<f64> {
+    self.values.get(&control).copied()
+  }
+
+  /// Requests a change of the control (shown right away, confirmed by the read back)
+  pub fn set(&mut self, control: CameraControl, value: f64) {
+    self.values.insert(control, value);
+    self.pending.push((control, value));
+  }
+
+  /// Requests changes of all controls of the preset
+  pub fn apply(&mut self, preset: &CameraPreset) {
+    for (control, value) in preset {
+      self.set(*control, *value);
+    }
+  }
+
+  /// Checks if the automatic mode of the control is on
+  pub fn is_auto(&self, control: CameraControl) -> bool {
+    control
+      .auto_control()
+      .and_then(|auto_control| Some((self.value(auto_control)?, auto_control.switch_values()?)))
+      .is_some_and(|(value, (off_value, _))| value != off_value)
+  }
+
+  /// Returns current values as a preset
+  pub fn preset(&self) -> CameraPreset {
+    self.values.clone()
+  }
+
+  /// Applies pending changes to the capture and reads the values back.
+  ///
+  /// Only the controls with a known range are read (if the camera reports any),
+  /// changes of unsupported controls are ignored by OpenCV.
+  pub fn sync(
+    &mut self,
+    capture: &mut VideoCapture,
+    ranges: &BTreeMap<CameraControl, ControlRange>,
+    is_read: bool,
+  ) {
+    let pending = std::mem::take(&mut self.pending);
+
+    if pending.is_empty() && is_read {
+      return;
+    }
+
+    for (control, value) in pending {
+      let _ = capture.set(control.property(), value);
+    }
+
+    self.values = CameraControl::ALL
+      .into_iter()
+      .filter(|control| ranges.is_empty() || ranges.contains_key(control))
+      .filter_map(|control| {
+        let value = capture.get(control.property()).ok()?;
+
+        // Without the ranges -1 is the only sign of an unsupported property
+        (!ranges.is_empty() || value != -1.0).then_some((control, value))
+      })
+      .collect();
+  }
+
+  /// Forgets the values of a closed camera
+  pub fn clear(&mut self) {
+    self.values.clear();
+    self.pending.clear();
+  }
+}
+
+/// Parses a preset of the config file (control names and values)
+pub fn parse_preset(values: &BTreeMap<String, f64>) -> Result<CameraPreset, String> {
+  values
+    .iter()
+    .map(|(name, value)| {
+      CameraControl::from_name(name)
+        .map(|control| (control, *value))
+        .ok_or_else(|| format!("unknown camera control {}", name))
+    })
+    .collect()
+}
@@ -0,0 +1,451 @@
+use std::{
+  collections::BTreeMap,
+  path::{Path, PathBuf},
+};
+
+use crate::controls::{CameraControl, ControlRange};
+
+#[cfg(target_os = "linux")]
+use v4l::{
+  capability, frameinterval::FrameIntervalEnum, framesize::FrameSizeEnum, video::Capture, Device,
+  FourCC,
+};
+
+/// Directory of the persistent camera device links (V4L2 only)
+#[cfg(target_os = "linux")]
+const STABLE_PATH_DIR: &str = "/dev/v4l/by-id";
+
+/// Highest probed device camera index (without V4L2)
+#[cfg(not(target_os = "linux"))]
+const MAX_CAMERA_ID: i32 = 10;
+
+/// Compressed pixel formats (decoding costs more CPU than converting a raw format)
+const COMPRESSED_FOURCCS: &[&str] = &["MJPG", "JPEG", "H264", "HEVC", "VP80", "VP90"];
+
+/// Capture resolution of a pixel format
+#[derive(Clone, Debug)]
+pub struct CameraResolution {
+  pub width: u32,
+  pub height: u32,
+
+  /// Supported frame rates (fastest first)
+  pub fps: Vec<f64>,
+}
+
+/// Pixel format supported by a camera
+#[derive(Clone, Debug)]
+pub struct CameraFormat {
+  /// Four character code (`MJPG`, `YUYV`, ...)
+  pub fourcc: String,
+
+  /// Format description given by the driver
+  pub description: String,
+
+  /// Supported resolutions (largest first)
+  pub resolutions: Vec<CameraResolution>,
+}
+
+/// Capture settings of a device camera (unset values are left to the camera)
+#[derive(PartialEq, Clone, Debug)]
+pub struct CaptureMode {
+  /// Four character code of the pixel format
+  pub fourcc: Option<String>,
+
+  /// Resolution (width, height)
+  pub resolution: Option<(u32, u32)>,
+
+  /// Frame rate
+  pub fps: Option<f64>,
+}
+
+impl CaptureMode {
+  /// Checks if the pixel format is compressed
+  fn is_compressed(&self) -> bool {
+    self
+      .fourcc
+      .as_deref()
+      .is_some_and(|fourcc| COMPRESSED_FOURCCS.contains(&fourcc))
+  }
+}
+
+impl std::fmt::Display for CaptureMode {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    let mut parts = Vec::new();
+
+    if let Some(fourcc) = self.fourcc.as_ref() {
+      parts.push(fourcc.clone());
+    }
+
+    if let Some((width, height)) = self.resolution {
+      parts.push(format!("{}x{}", width, height));
+    }
+
+    if let Some(fps) = self.fps {
+      parts.push(format!("{}fps", (fps * 100.0).round() / 100.0));
+    }
+
+    if parts.is_empty() {
+      parts.push(String::from("default"));
+    }
+
+    write!(f, "{}", parts.join(" "))
+  }
+}
+
+/// Device camera with its capabilities
+#[derive(Clone, Debug)]
+pub struct CameraDevice {
+  /// Camera index (`/dev/video{id}`)
+  pub id: i32,
+
+  /// Device name (`Integrated Camera`)
+  pub name: String,
+
+  /// Device node (`/dev/video0`)
+  pub path: Option<PathBuf>,
+
+  /// Persistent device link which survives reboots (`/dev/v4l/by-id/...`)
+  pub stable_path: Option<PathBuf>,
+
+  /// Bus of the device (`usb-0000:00:14.0-8`)
+  pub bus_info: Option<String>,
+
+  /// Supported pixel formats
+  pub formats: Vec<CameraFormat>,
+
+  /// Value ranges of the supported controls (empty if unknown)
+  pub controls: BTreeMap<CameraControl, ControlRange>,
+}
+
+impl CameraDevice {
+  /// Creates a camera known only by its index
+  #[cfg(not(target_os = "linux"))]
+  fn from_id(id: i32) -> Self {
+    Self {
+      id,
+      name: format!("Camera {}", id),
+      path: None,
+      stable_path: None,
+      bus_info: None,
+      formats: Vec::new(),
+      controls: BTreeMap::new(),
+    }
+  }
+
+  /// Checks if the camera name or the bus contains the given name (case insensitive)
+  pub fn matches_name(&self, name: &str) -> bool {
+    let name = name.to_lowercase();
+
+    self.name.to_lowercase().contains(&name)
+      || self
+        .bus_info
+        .as_ref()
+        .is_some_and(|bus_info| bus_info.to_lowercase().contains(&name))
+  }
+
+  /// Checks if the path (a device node or a link to it) points to the camera
+  pub fn matches_path(&self, path: &Path) -> bool {
+    let Some(device_path) = self.path.as_ref() else {
+      return false;
+    };
+
+    match (path.canonicalize(), device_path.canonicalize()) {
+      (Ok(path), Ok(device_path)) => path == device_path,
+      _ => path == device_path,
+    }
+  }
+
+  /// Returns all capture modes (each resolution with its fastest frame rate)
+  pub fn capture_modes(&self) -> Vec<CaptureMode> {
+    self
+      .formats
+      .iter()
+      .flat_map(|format| {
+        format.resolutions.iter().map(|resolution| CaptureMode {
+          fourcc: Some(format.fourcc.clone()),
+          resolution: Some((resolution.width, resolution.height)),
+          fps: resolution.fps.first().copied(),
+        })
+      })
+      .collect()
+  }
+
+  /// Picks the smallest capture mode which covers the target size (the largest one if none does).
+  ///
+  /// A mode reaching the frame rate is preferred, then a raw pixel format
+  /// (it's cheaper to convert than a compressed one). The frame rate is the slowest
+  /// one reaching the requested rate. Returns None if the capabilities are unknown.
+  pub fn best_capture_mode(
+    &self,
+    target: (u32, u32),
+    fourcc: Option<&str>,
+    fps: f64,
+  ) -> Option<CaptureMode> {
+    let candidates = self
+      .formats
+      .iter()
+      .filter(|format| fourcc.is_none_or(|fourcc| format.fourcc.eq_ignore_ascii_case(fourcc)))
+      .flat_map(|format| {
+        format.resolutions.iter().map(move |resolution| {
+          let mode_fps = resolution
+            .fps
+            .iter()
+            .rev()
+            .find(|mode_fps| **mode_fps >= fps)
+            .or(resolution.fps.first())
+            .copied();
+
+          CaptureMode {
+            fourcc: Some(format.fourcc.clone()),
+            resolution: Some((resolution.width, resolution.height)),
+            fps: mode_fps,
+          }
+        })
+      })
+      .collect::<Vec<_>>();
+
+    let area = |mode: &CaptureMode| mode.resolution.map_or(0, |(width, height)| width * height);
+    let covers = |mode: &CaptureMode| {
+      mode
+        .resolution
+        .is_some_and(|(width, height)| width >= target.0 && height >= target.1)
+    };
+
+    let is_slow = |mode: &CaptureMode| mode.fps.is_some_and(|mode_fps| mode_fps < fps);
+
+    let covering = candidates
+      .iter()
+      .filter(|mode| covers(mode))
+      .min_by_key(|mode| (is_slow(mode), area(mode), mode.is_compressed()));
+
+    covering
+      .or_else(|| {
+        candidates
+          .iter()
+          .max_by_key(|mode| (!is_slow(mode), area(mode), !mode.is_compressed()))
+      })
+      .cloned()
+  }
+
+  /// Returns a short capability summary (the largest resolution of each format)
+  pub fn summary(&self) -> String {
+    self
+      .formats
+      .iter()
+      .map(|format| match format.resolutions.first() {
+        Some(resolution) => format!("{} {}", format.fourcc, resolution),
+        None => format.fourcc.clone(),
+      })
+      .collect::<Vec<_>>()
+      .join(", ")
+  }
+}
+
+impl std::fmt::Display for CameraResolution {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "{}x{}", self.width, self.height)?;
+
+    if !self.fps.is_empty() {
+      let fps = self
+        .fps
+        .iter()
+        .map(|fps| format!("{}", (fps * 100.0).round() / 100.0))
+        .collect::<Vec<_>>()
+        .join("/");
+
+      write!(f, "@{}", fps)?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Enumerates the V4L2 capture devices.
+///
+/// Every device is queried on each scan, so changed capabilities are picked up.
+/// Only the busy camera (streamed by the frame handler) is kept as is while its node
+/// has the same name, the stream isn't disturbed by the queries.
+/// Blocks until the devices are queried, call it outside of the async runtime.
+#[cfg(target_os = "linux")]
+pub fn enumerate(known: &[CameraDevice], busy_id: Option<i32>) -> Vec<CameraDevice> {
+  let mut nodes = v4l::context::enum_devices();
+  nodes.sort_by_key(|node| node.index());
+
+  nodes
+    .into_iter()
+    .filter_map(|node| {
+      let id = node.index() as i32;
+      let name = node.name();
+
+      if let Some(device) = known.iter().find(|device| {
+        busy_id == Some(id)
+          && device.id == id
+          && name.as_ref().is_some_and(|name| *name == device.name)
+      }) {
+        return Some(device.clone());
+      }
+
+      query(id, node.path())
+    })
+    .collect()
+}
+
+/// Queries a device node, returns None for nodes without a video capture
+/// (metadata nodes of the same camera)
+#[cfg(target_os = "linux")]
+fn query(id: i32, path: &Path) -> Option<CameraDevice> {
+  let device = Device::with_path(path).ok()?;
+  let caps = device.query_caps().ok()?;
+
+  if !caps.capabilities.contains(capability::Flags::VIDEO_CAPTURE) {
+    return None;
+  }
+
+  let formats = device
+    .enum_formats()
+    .ok()?
+    .into_iter()
+    .map(|format| CameraFormat {
+      fourcc: fourcc_name(&format.fourcc),
+      description: format.description,
+      resolutions: query_resolutions(&device, format.fourcc),
+    })
+    .collect::<Vec<_>>();
+
+  if formats.is_empty() {
+    return None;
+  }
+
+  Some(CameraDevice {
+    id,
+    name: caps.card,
+    path: Some(path.to_path_buf()),
+    stable_path: stable_path(path),
+    bus_info: Some(caps.bus).filter(|bus| !bus.is_empty()),
+    formats,
+    controls: query_controls(&device),
+  })
+}
+
+/// Queries the value ranges of the supported controls
+#[cfg(target_os = "linux")]
+fn query_controls(device: &Device) -> BTreeMap<CameraControl, ControlRange> {
+  let descriptions = device.query_controls().unwrap_or_default();
+
+  CameraControl::ALL
+    .into_iter()
+    .filter_map(|control| {
+      let description = descriptions
+        .iter()
+        .find(|description| description.id == control.v4l2_id())?;
+
+      // Disabled controls are reported too
+      if description.flags.contains(v4l::control::Flags::DISABLED) {
+        return None;
+      }
+
+      let range = ControlRange {
+        min: description.minimum as f64,
+        max: description.maximum as f64,
+        step: (description.step as f64).max(1.0),
+        default: description.default as f64,
+      };
+
+      Some((control, range))
+    })
+    .collect()
+}
+
+/// Queries the resolutions (and the frame rates) of a pixel format.
+///
+/// A stepwise range is reported by its bounds only.
+#[cfg(target_os = "linux")]
+fn query_resolutions(device: &Device, fourcc: FourCC) -> Vec<CameraResolution> {
+  let sizes = device
+    .enum_framesizes(fourcc)
+    .unwrap_or_default()
+    .into_iter()
+    .flat_map(|frame_size| match frame_size.size {
+      FrameSizeEnum::Discrete(size) => vec![(size.width, size.height)],
+      FrameSizeEnum::Stepwise(size) => vec![
+        (size.max_width, size.max_height),
+        (size.min_width, size.min_height),
+      ],
+    })
+    .collect::<Vec<_>>();
+
+  let mut resolutions = sizes
+    .into_iter()
+    .map(|(width, height)| {
+      let mut fps = device
+        .enum_frameintervals(fourcc, width, height)
+        .unwrap_or_default()
+        .into_iter()
+        .flat_map(|frame_interval| match frame_interval.interval {
+          FrameIntervalEnum::Discrete(interval) => vec![interval],
+          FrameIntervalEnum::Stepwise(interval) => vec![interval.min, interval.max],
+        })
+        .filter(|interval| interval.numerator > 0)
+        .map(|interval| interval.denominator as f64 / interval.numerator as f64)
+        .collect::<Vec<_>>();
+
+      fps.sort_by(|a, b| b.total_cmp(a));
+      fps.dedup();
+
+      CameraResolution { width, height, fps }
+    })
+    .collect::<Vec<_>>();
+
+  resolutions.sort_by_key(|resolution| std::cmp::Reverse(resolution.width * resolution.height));
+  resolutions.dedup_by_key(|resolution| (resolution.width, resolution.height));
+
+  resolutions
+}
+
+/// Returns a printable four character code
+#[cfg(target_os = "linux")]
+fn fourcc_name(fourcc: &FourCC) -> String {
+  fourcc
+    .str()
+    .map(|name| name.trim().to_string())
+    .unwrap_or_else(|_| format!("{:?}", fourcc.repr))
+}
+
+/// Finds a persistent link of the device node
+#[cfg(target_os = "linux")]
+fn stable_path(path: &Path) -> Option<PathBuf> {
+  let path = path.canonicalize().ok()?;
+
+  std::fs::read_dir(STABLE_PATH_DIR)
+    .ok()?
+    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+    .find(|link| link.canonicalize().is_ok_and(|link_path| link_path == path))
+}
+
+/// Probes the device cameras by opening them.
+///
+/// Every camera is probed on each scan, so a disconnected one is dropped.
+/// Only the busy camera (streamed by the frame handler) is kept as is,
+/// it can't be opened twice and its stream fails once it's disconnected.
+/// Blocks until the cameras are probed, call it outside of the async runtime.
+#[cfg(not(target_os = "linux"))]
+pub fn enumerate(known: &[CameraDevice], busy_id: Option<i32>) -> Vec<CameraDevice> {
+  use opencv::{
+    prelude::*,
+    videoio::{self, VideoCapture},
+  };
+
+  (0..=MAX_CAMERA_ID)
+    .filter_map(|id| {
+      if busy_id == Some(id) {
+        let device = known.iter().find(|device| device.id == id).cloned();
+        return Some(device.unwrap_or_else(|| CameraDevice::from_id(id)));
+      }
+
+      VideoCapture::new(id, videoio::CAP_ANY)
+        .and_then(|cam| cam.is_opened())
+        .unwrap_or(false)
+        .then(|| CameraDevice::from_id(id))
+    })
+    .collect()
+}
@@ -0,0 +1,391 @@
+use std::sync::OnceLock;
+
+use opencv::{core::Mat, prelude::*};
+
+/// Side of the tiled blue noise texture in pixels
+const BLUE_NOISE_SIZE: usize = 32;
+
+/// Spread of the blue noise energy filter (void-and-cluster)
+const BLUE_NOISE_SIGMA: f32 = 1.5;
+
+/// Amplitude of the ordered dithering of palette colors (the palette isn't evenly spaced)
+const PALETTE_SPREAD: f32 = 32.0;
+
+/// Floyd–Steinberg error diffusion (dx, dy, weight)
+const FLOYD_STEINBERG: &[(isize, usize, f32)] = &[
+  (1, 0, 7.0 / 16.0),
+  (-1, 1, 3.0 / 16.0),
+  (0, 1, 5.0 / 16.0),
+  (1, 1, 1.0 / 16.0),
+];
+
+/// Atkinson error diffusion (only 3/4 of the error is spread, keeps the contrast)
+const ATKINSON: &[(isize, usize, f32)] = &[
+  (1, 0, 1.0 / 8.0),
+  (2, 0, 1.0 / 8.0),
+  (-1, 1, 1.0 / 8.0),
+  (0, 1, 1.0 / 8.0),
+  (1, 1, 1.0 / 8.0),
+  (0, 2, 1.0 / 8.0),
+];
+
+/// Sierra (three row) error diffusion
+const SIERRA: &[(isize, usize, f32)] = &[
+  (1, 0, 5.0 / 32.0),
+  (2, 0, 3.0 / 32.0),
+  (-2, 1, 2.0 / 32.0),
+  (-1, 1, 4.0 / 32.0),
+  (0, 1, 5.0 / 32.0),
+  (1, 1, 4.0 / 32.0),
+  (2, 1, 2.0 / 32.0),
+  (-1, 2, 2.0 / 32.0),
+  (0, 2, 3.0 / 32.0),
+  (1, 2, 2.0 / 32.0),
+];
+
+/// Dithering of the reduced tone (or palette) modes
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Dithering {
+  None,
+  FloydSteinberg,
+  Atkinson,
+  Sierra,
+  Bayer2,
+  Bayer4,
+  Bayer8,
+  BlueNoise,
+}
+
+impl Dithering {
+  /// All dithering methods in the switch order
+  pub const ALL: [Dithering; 8] = [
+    Dithering::None,
+    Dithering::FloydSteinberg,
+    Dithering::Atkinson,
+    Dithering::Sierra,
+    Dithering::Bayer2,
+    Dithering::Bayer4,
+    Dithering::Bayer8,
+    Dithering::BlueNoise,
+  ];
+
+  /// Returns a dithering name (used by the command line and the config file)
+  pub fn name(&self) -> &'static str {
+    match self {
+      Dithering::None => "none",
+      Dithering::FloydSteinberg => "floyd-steinberg",
+      Dithering::Atkinson => "atkinson",
+      Dithering::Sierra => "sierra",
+      Dithering::Bayer2 => "bayer2",
+      Dithering::Bayer4 => "bayer4",
+      Dithering::Bayer8 => "bayer8",
+      Dithering::BlueNoise => "blue-noise",
+    }
+  }
+
+  /// Finds a dithering by the name
+  pub fn from_name(name: &str) -> Option<Self> {
+    let name = name.to_lowercase();
+    Self::ALL
+      .into_iter()
+      .find(|dithering| dithering.name() == name)
+  }
+
+  /// Returns a next dithering (wraps around)
+  pub fn next(&self) -> Self {
+    let index = Self::ALL
+      .iter()
+      .position(|dithering| dithering == self)
+      .unwrap_or(0);
+
+    Self::ALL[(index + 1) % Self::ALL.len()]
+  }
+
+  /// Returns an error diffusion kernel (None for the ordered dithering)
+  fn kernel(&self) -> Option<&'static [(isize, usize, f32)]> {
+    match self {
+      Dithering::FloydSteinberg => Some(FLOYD_STEINBERG),
+      Dithering::Atkinson => Some(ATKINSON),
+      Dithering::Sierra => Some(SIERRA),
+      _ => None,
+    }
+  }
+
+  /// Returns an ordered dithering offset of the pixel (-0.5 - 0.5)
+  fn offset(&self, x: usize, y: usize) -> f32 {
+    let (rank, count) = match self {
+      Dithering::Bayer2 => (bayer(2, x % 2, y % 2), 4),
+      Dithering::Bayer4 => (bayer(4, x % 4, y % 4), 16),
+      Dithering::Bayer8 => (bayer(8, x % 8, y % 8), 64),
+      Dithering::BlueNoise => {
+        let index = (y % BLUE_NOISE_SIZE) * BLUE_NOISE_SIZE + x % BLUE_NOISE_SIZE;
+        (
+          blue_noise()[index] as usize,
+          BLUE_NOISE_SIZE * BLUE_NOISE_SIZE,
+        )
+      }
+      _ => return 0.0,
+    };
+
+    (rank as f32 + 0.5) / count as f32 - 0.5
+  }
+
+  /// Reduces a continuous grayscale frame into evenly spaced tone levels
+  pub fn dither_frame(&self, gray_frame: &mut Mat, levels: usize) -> opencv::Result<()> {
+    let width = gray_frame.cols() as usize;
+    self.dither_gray(gray_frame.data_bytes_mut()?, width, levels);
+
+    Ok(())
+  }
+
+  /// Reduces grayscale pixels into evenly spaced tone levels (0 and 255 included)
+  pub fn dither_gray(&self, gray: &mut [u8], width: usize, levels: usize) {
+    let step = 255.0 / (levels.max(2) - 1) as f32;
+    let quantize = |value: f32| ((value / step).round() * step).clamp(0.0, 255.0);
+
+    let Some(kernel) = self.kernel() else {
+      for (i, pixel) in gray.iter_mut().enumerate() {
+        let offset = self.offset(i % width, i / width) * step;
+        *pixel = quantize(*pixel as f32 + offset) as u8;
+      }
+      return;
+    };
+
+    let mut values = gray.iter().map(|pixel| *pixel as f32).collect::<Vec<_>>();
+
+    for_each_serpentine(gray.len(), width, |i, x, y, is_reversed| {
+      let value = values[i];
+      let quantized = quantize(value);
+      gray[i] = quantized as u8;
+
+      for (target, weight) in diffusion_targets(kernel, x, y, width, gray.len(), is_reversed) {
+        values[target] += (value - quantized) * weight;
+      }
+    });
+  }
+
+  /// Maps RGB pixels to a palette with the dithering, returns a palette index of each pixel
+  pub fn dither_palette(&self, rgb: &[u8], width: usize, palette: &[[u8; 3]]) -> Vec<u8> {
+    let mut nearest = NearestColor::new(palette);
+    let len = rgb.len() / 3;
+
+    let Some(kernel) = self.kernel() else {
+      return (0..len)
+        .map(|i| {
+          let offset = self.offset(i % width, i / width) * PALETTE_SPREAD;
+          let color = std::array::from_fn(|channel| rgb[i * 3 + channel] as f32 + offset);
+          nearest.find(color)
+        })
+        .collect();
+    };
+
+    let mut values = rgb.iter().map(|value| *value as f32).collect::<Vec<_>>();
+    let mut indices = vec![0u8; len];
+
+    for_each_serpentine(len, width, |i, x, y, is_reversed| {
+      let color: [f32; 3] = std::array::from_fn(|channel| values[i * 3 + channel]);
+      let index = nearest.find(color);
+      indices[i] = index;
+
+      let palette_color = palette[index as usize];
+
+      for (target, weight) in diffusion_targets(kernel, x, y, width, len, is_reversed) {
+        for channel in 0..3 {
+          let error = color[channel] - palette_color[channel] as f32;
+          values[target * 3 + channel] += error * weight;
+        }
+      }
+    });
+
+    indices
+  }
+}
+
+/// Nearest palette color lookup (cached by 15-bit colors)
+struct NearestColor<'a> {
+  palette: &'a [[u8; 3]],
+  cache: Vec<Option<u8>>,
+}
+
+impl<'a> NearestColor<'a> {
+  fn new(palette: &'a [[u8; 3]]) -> Self {
+    Self {
+      palette,
+      cache: vec![None; 1 << 15],
+    }
+  }
+
+  /// Returns a palette index of the nearest color (channels are clamped)
+  fn find(&mut self, color: [f32; 3]) -> u8 {
+    let color = color.map(|channel| channel.round().clamp(0.0, 255.0) as u8);
+    let key =
+      ((color[0] as usize >> 3) << 10) | ((color[1] as usize >> 3) << 5) | (color[2] as usize >> 3);
+
+    if let Some(index) = self.cache[key] {
+      return index;
+    }
+
+    let index = self
+      .palette
+      .iter()
+      .enumerate()
+      .min_by_key(|(_, palette_color)| {
+        (0..3)
+          .map(|channel| (color[channel] as i32 - palette_color[channel] as i32).pow(2))
+          .sum::<i32>()
+      })
+      .map(|(index, _)| index as u8)
+      .unwrap_or(0);
+
+    self.cache[key] = Some(index);
+    index
+  }
+}
+
+/// Visits pixels row by row, odd rows from the right (spreads the error evenly)
+fn for_each_serpentine(len: usize, width: usize, mut visit: impl FnMut(usize, usize, usize, bool)) {
+  let width = width.max(1);
+
+  for y in 0..len.div_ceil(width) {
+    let is_reversed = y % 2 == 1;
+    let row_width = width.min(len - y * width);
+
+    for dx in 0..row_width {
+      let x = if is_reversed { row_width - 1 - dx } else { dx };
+      visit(y * width + x, x, y, is_reversed);
+    }
+  }
+}
+
+/// Returns pixel indices (with weights) receiving the error of the pixel
+fn diffusion_targets(
+  kernel: &'static [(isize, usize, f32)],
+  x: usize,
+  y: usize,
+  width: usize,
+  len: usize,
+  is_reversed: bool,
+) -> impl Iterator<Item = (usize, f32)> {
+  kernel.iter().filter_map(move |(dx, dy, weight)| {
+    let dx = if is_reversed { -dx } else { *dx };
+    let target_x = x
+      .checked_add_signed(dx)
+      .filter(|target_x| *target_x < width)?;
+    let target = (y + dy) * width + target_x;
+
+    (target < len).then_some((target, *weight))
+  })
+}
+
+/// Returns a rank of the pixel in the Bayer matrix of the size (a power of two)
+fn bayer(size: usize, x: usize, y: usize) -> usize {
+  if size <= 1 {
+    return 0;
+  }
+
+  let half = size / 2;
+  let quadrant = [[0, 2], [3, 1]][y / half][x / half];
+
+  4 * bayer(half, x % half, y % half) + quadrant
+}
+
+/// Returns ranks of the blue noise texture (computed once)
+fn blue_noise() -> &'static [u16] {
+  static BLUE_NOISE: OnceLock<Vec<u16>> = OnceLock::new();
+  BLUE_NOISE.get_or_init(void_and_cluster)
+}
+
+/// Generates a tileable blue noise texture (Ulichney's void-and-cluster).
+///
+/// Points are ranked by removing the tightest clusters of an initial pattern
+/// and by filling the largest voids, so every threshold gives even spacing.
+fn void_and_cluster() -> Vec<u16> {
+  let size = BLUE_NOISE_SIZE;
+  let len = size * size;
+
+  // Energy of a point by the wrapped offset
+  let weights = (0..len)
+    .map(|i| {
+      let dx = (i % size).min(size - i % size) as f32;
+      let dy = (i / size).min(size - i / size) as f32;
+      (-(dx * dx + dy * dy) / (2.0 * BLUE_NOISE_SIGMA * BLUE_NOISE_SIGMA)).exp()
+    })
+    .collect::<Vec<_>>();
+
+  let toggle = |is_set: &mut [bool], energy: &mut [f32], i: usize, is_on: bool| {
+    is_set[i] = is_on;
+    let sign = if is_on { 1.0 } else { -1.0 };
+
+    for (j, pixel_energy) in energy.iter_mut().enumerate() {
+      let dx = (j % size + size - i % size) % size;
+      let dy = (j / size + size - i / size) % size;
+      *pixel_energy += sign * weights[dy * size + dx];
+    }
+  };
+
+  let tightest_cluster = |is_set: &[bool], energy: &[f32]| {
+    (0..len)
+      .filter(|i| is_set[*i])
+      .max_by(|lhs, rhs| energy[*lhs].total_cmp(&energy[*rhs]))
+      .unwrap_or(0)
+  };
+
+  let largest_void = |is_set: &[bool], energy: &[f32]| {
+    (0..len)
+      .filter(|i| !is_set[*i])
+      .min_by(|lhs, rhs| energy[*lhs].total_cmp(&energy[*rhs]))
+      .unwrap_or(0)
+  };
+
+  let mut is_set = vec![false; len];
+  let mut energy = vec![0.0; len];
+
+  // Initial pattern of a tenth of the points (a fixed seed, the texture is always the same)
+  let mut state = 0x9e37_79b9u32;
+  let mut count = 0;
+
+  while count < len / 10 {
+    state ^= state << 13;
+    state ^= state >> 17;
+    state ^= state << 5;
+
+    let i = state as usize % len;
+    if !is_set[i] {
+      toggle(&mut is_set, &mut energy, i, true);
+      count += 1;
+    }
+  }
+
+  // Moves the tightest clusters into the largest voids until the pattern settles
+  for _ in 0..len {
+    let cluster = tightest_cluster(&is_set, &energy);
+    toggle(&mut is_set, &mut energy, cluster, false);
+
+    let void = largest_void(&is_set, &energy);
+    toggle(&mut is_set, &mut energy, void, true);
+
+    if void == cluster {
+      break;
+    }
+  }
+
+  let mut ranks = vec![0u16; len];
+
+  let (initial_set, initial_energy) = (is_set.clone(), energy.clone());
+
+  for rank in (0..count).rev() {
+    let cluster = tightest_cluster(&is_set, &energy);
+    toggle(&mut is_set, &mut energy, cluster, false);
+    ranks[cluster] = rank as u16;
+  }
+
+  let (mut is_set, mut energy) = (initial_set, initial_energy);
+
+  for rank in count..len {
+    let void = largest_void(&is_set, &energy);
+    toggle(&mut is_set, &mut energy, void, true);
+    ranks[void] = rank as u16;
+  }
+
+  ranks
+}
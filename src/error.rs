@@ -0,0 +1,51 @@
+use std::fmt;
+
+/// Failure of the frame pipeline.
+///
+/// Reported to the app as `AppEvent::Error` and shown in the camera block,
+/// the frame handler waits for a retry or a switch of the input.
+#[derive(Debug)]
+pub enum TuicamError {
+  /// Frame source can't be opened (unplugged camera, missing file, ...)
+  Open(String, opencv::Error),
+
+  /// Frame source can't be read
+  Read(String, opencv::Error),
+
+  /// Frame source returned no frame (the camera stopped streaming)
+  NoFrame(String),
+
+  /// Frame can't be resized or converted
+  Process(opencv::Error),
+
+  /// Frame can't be encoded by a graphics protocol
+  Encode(opencv::Error),
+}
+
+impl fmt::Display for TuicamError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      TuicamError::Open(description, err) => {
+        write!(f, "Unable to open {}: {}", description, err.message)
+      }
+      TuicamError::Read(description, err) => {
+        write!(f, "Unable to read {}: {}", description, err.message)
+      }
+      TuicamError::NoFrame(description) => write!(f, "No frame received from {}", description),
+      TuicamError::Process(err) => write!(f, "Unable to process the frame: {}", err.message),
+      TuicamError::Encode(err) => write!(f, "Unable to encode the frame: {}", err.message),
+    }
+  }
+}
+
+impl std::error::Error for TuicamError {
+  fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+    match self {
+      TuicamError::Open(_, err)
+      | TuicamError::Read(_, err)
+      | TuicamError::Process(err)
+      | TuicamError::Encode(err) => Some(err),
+      TuicamError::NoFrame(_) => None,
+    }
+  }
+}
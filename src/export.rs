@@ -0,0 +1,356 @@
+use std::{
+  fmt::Write,
+  path::{Path, PathBuf},
+};
+
+use ratatui::{
+  style::{Color, Style},
+  text::{Line, Text},
+};
+
+/// Foreground color of image exports (HTML, SVG and GIF) used for the terminal default color
+pub(crate) const DEFAULT_FG: (u8, u8, u8) = (229, 229, 229);
+
+/// Background color of image exports (HTML, SVG and GIF) used for the terminal default color
+pub(crate) const DEFAULT_BG: (u8, u8, u8) = (16, 16, 16);
+
+/// SVG cell size in pixels (width, height)
+const SVG_CELL_SIZE: (usize, usize) = (8, 16);
+
+/// SVG font size in pixels
+const SVG_FONT_SIZE: usize = 14;
+
+/// Standard xterm colors (0 - 15)
+const ANSI_COLORS: [(u8, u8, u8); 16] = [
+  (0, 0, 0),
+  (205, 0, 0),
+  (0, 205, 0),
+  (205, 205, 0),
+  (0, 0, 238),
+  (205, 0, 205),
+  (0, 205, 205),
+  (229, 229, 229),
+  (127, 127, 127),
+  (255, 0, 0),
+  (0, 255, 0),
+  (255, 255, 0),
+  (92, 92, 255),
+  (255, 0, 255),
+  (0, 255, 255),
+  (255, 255, 255),
+];
+
+/// Export file format
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum ExportFormat {
+  Ansi,
+  Plain,
+  Html,
+  Svg,
+}
+
+impl ExportFormat {
+  /// All formats (all of them are written by one export)
+  pub const ALL: [ExportFormat; 4] = [
+    ExportFormat::Ansi,
+    ExportFormat::Plain,
+    ExportFormat::Html,
+    ExportFormat::Svg,
+  ];
+
+  /// Returns a format name (used by the CLI)
+  pub fn name(&self) -> &'static str {
+    match self {
+      ExportFormat::Ansi => "ansi",
+      ExportFormat::Plain => "txt",
+      ExportFormat::Html => "html",
+      ExportFormat::Svg => "svg",
+    }
+  }
+
+  /// Finds a format by the name
+  pub fn from_name(name: &str) -> Option<Self> {
+    let name = name.to_lowercase();
+    Self::ALL.into_iter().find(|format| format.name() == name)
+  }
+
+  /// Returns a file extension of the format
+  pub fn extension(&self) -> &'static str {
+    match self {
+      ExportFormat::Ansi => "ans",
+      ExportFormat::Plain => "txt",
+      ExportFormat::Html => "html",
+      ExportFormat::Svg => "svg",
+    }
+  }
+
+  /// Serializes the frame into the format
+  pub fn render(&self, text: &Text) -> String {
+    match self {
+      ExportFormat::Ansi => to_ansi(text),
+      ExportFormat::Plain => to_plain(text),
+      ExportFormat::Html => to_html(text),
+      ExportFormat::Svg => to_svg(text),
+    }
+  }
+}
+
+/// Writes the rendered frame as ANSI, plain text, HTML and SVG files.
+///
+/// The files share the path stem, returns the written paths.
+pub fn export_all(text: &Text, stem: &Path) -> std::io::Result<Vec<PathBuf>> {
+  if let Some(dir) = stem.parent() {
+    std::fs::create_dir_all(dir)?;
+  }
+
+  ExportFormat::ALL
+    .iter()
+    .map(|format| {
+      let path = stem.with_extension(format.extension());
+      std::fs::write(&path, format.render(text))?;
+
+      Ok(path)
+    })
+    .collect()
+}
+
+/// Serializes the frame into ANSI escaped text (24-bit colors)
+pub fn to_ansi(text: &Text) -> String {
+  let mut ansi = String::new();
+
+  for line in &text.lines {
+    for (style, content) in style_runs(line) {
+      ansi.push_str("\x1b[0");
+
+      if let Some((r, g, b)) = style.fg.and_then(color_rgb) {
+        let _ = write!(ansi, ";38;2;{};{};{}", r, g, b);
+      }
+
+      if let Some((r, g, b)) = style.bg.and_then(color_rgb) {
+        let _ = write!(ansi, ";48;2;{};{};{}", r, g, b);
+      }
+
+      ansi.push('m');
+      ansi.push_str(&content);
+    }
+
+    ansi.push_str("\x1b[0m\n");
+  }
+
+  ansi
+}
+
+/// Serializes the frame into plain text (glyphs only)
+pub fn to_plain(text: &Text) -> String {
+  text
+    .lines
+    .iter()
+    .map(|line| {
+      let content = line
+        .spans
+        .iter()
+        .map(|span| span.content.as_ref())
+        .collect::<String>();
+
+      content.trim_end().to_string() + "\n"
+    })
+    .collect()
+}
+
+/// Serializes the frame into a self-contained HTML page with styled spans
+pub fn to_html(text: &Text) -> String {
+  let mut html = String::new();
+
+  let _ = write!(
+    html,
+    "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>tuicam</title>\n\
+     <style>pre {{ display: inline-block; margin: 0; padding: 1em; \
+     font-family: monospace; line-height: 1; color: {}; background: {}; }}</style>\n\
+     </head>\n<body>\n<pre>",
+    css_color(DEFAULT_FG),
+    css_color(DEFAULT_BG)
+  );
+
+  for line in &text.lines {
+    for (style, content) in style_runs(line) {
+      let fg = style.fg.and_then(color_rgb);
+      let bg = style.bg.and_then(color_rgb);
+
+      if fg.is_none() && bg.is_none() {
+        html.push_str(&escape_xml(&content));
+        continue;
+      }
+
+      html.push_str("<span style=\"");
+
+      if let Some(fg) = fg {
+        let _ = write!(html, "color: {};", css_color(fg));
+      }
+
+      if let Some(bg) = bg {
+        let _ = write!(html, "background: {};", css_color(bg));
+      }
+
+      let _ = write!(html, "\">{}</span>", escape_xml(&content));
+    }
+
+    html.push('\n');
+  }
+
+  html.push_str("</pre>\n</body>\n</html>\n");
+
+  html
+}
+
+/// Serializes the frame into an SVG image (a text element per style run)
+pub fn to_svg(text: &Text) -> String {
+  let (cell_width, cell_height) = SVG_CELL_SIZE;
+
+  let cols = text
+    .lines
+    .iter()
+    .map(|line| line.width())
+    .max()
+    .unwrap_or(0);
+
+  let width = cols * cell_width;
+  let height = text.lines.len() * cell_height;
+
+  let mut svg = String::new();
+
+  let _ = write!(
+    svg,
+    "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" \
+     viewBox=\"0 0 {width} {height}\" font-family=\"monospace\" font-size=\"{}\">\n\
+     <rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+    SVG_FONT_SIZE,
+    css_color(DEFAULT_BG)
+  );
+
+  for (row, line) in text.lines.iter().enumerate() {
+    let y = row * cell_height;
+    let mut col = 0;
+
+    for (style, content) in style_runs(line) {
+      let x = col * cell_width;
+      let run_width = content.chars().count() * cell_width;
+
+      if let Some(bg) = style.bg.and_then(color_rgb) {
+        let _ = writeln!(
+          svg,
+          "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+          x,
+          y,
+          run_width,
+          cell_height,
+          css_color(bg)
+        );
+      }
+
+      if !content.trim().is_empty() {
+        let fg = style.fg.and_then(color_rgb).unwrap_or(DEFAULT_FG);
+
+        // The text length keeps glyphs on the cell grid regardless of the font
+        let _ = writeln!(
+          svg,
+          "<text x=\"{}\" y=\"{}\" fill=\"{}\" textLength=\"{}\" \
+           lengthAdjust=\"spacingAndGlyphs\" xml:space=\"preserve\">{}</text>",
+          x,
+          y + cell_height * 3 / 4,
+          css_color(fg),
+          run_width,
+          escape_xml(&content)
+        );
+      }
+
+      col += content.chars().count();
+    }
+  }
+
+  svg.push_str("</svg>\n");
+
+  svg
+}
+
+/// Joins neighbouring spans of the same style
+fn style_runs(line: &Line) -> Vec<(Style, String)> {
+  let mut runs: Vec<(Style, String)> = Vec::new();
+
+  for span in &line.spans {
+    let style = line.style.patch(span.style);
+
+    match runs.last_mut() {
+      Some((last_style, content)) if *last_style == style => content.push_str(&span.content),
+      _ => runs.push((style, span.content.to_string())),
+    }
+  }
+
+  runs
+}
+
+/// Returns RGB values of the color (None for the terminal default color)
+pub(crate) fn color_rgb(color: Color) -> Option<(u8, u8, u8)> {
+  let index = match color {
+    Color::Reset => return None,
+    Color::Rgb(r, g, b) => return Some((r, g, b)),
+    Color::Indexed(index) => index,
+    Color::Black => 0,
+    Color::Red => 1,
+    Color::Green => 2,
+    Color::Yellow => 3,
+    Color::Blue => 4,
+    Color::Magenta => 5,
+    Color::Cyan => 6,
+    Color::Gray => 7,
+    Color::DarkGray => 8,
+    Color::LightRed => 9,
+    Color::LightGreen => 10,
+    Color::LightYellow => 11,
+    Color::LightBlue => 12,
+    Color::LightMagenta => 13,
+    Color::LightCyan => 14,
+    Color::White => 15,
+  };
+
+  Some(indexed_rgb(index))
+}
+
+/// Returns RGB values of a xterm 256 color palette index
+fn indexed_rgb(index: u8) -> (u8, u8, u8) {
+  match index {
+    0..=15 => ANSI_COLORS[index as usize],
+    // 6x6x6 color cube
+    16..=231 => {
+      let level = |value: u8| if value == 0 { 0 } else { 55 + value * 40 };
+      let index = index - 16;
+      (level(index / 36), level(index / 6 % 6), level(index % 6))
+    }
+    // Grayscale ramp
+    _ => {
+      let value = 8 + (index - 232) * 10;
+      (value, value, value)
+    }
+  }
+}
+
+/// Returns a CSS hex color
+fn css_color((r, g, b): (u8, u8, u8)) -> String {
+  format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
+
+/// Escapes XML (and HTML) special characters
+fn escape_xml(content: &str) -> String {
+  let mut escaped = String::with_capacity(content.len());
+
+  for c in content.chars() {
+    match c {
+      '&' => escaped.push_str("&amp;"),
+      '<' => escaped.push_str("&lt;"),
+      '>' => escaped.push_str("&gt;"),
+      '"' => escaped.push_str("&quot;"),
+      _ => escaped.push(c),
+    }
+  }
+
+  escaped
+}
@@ -0,0 +1,490 @@
+use std::{io::Write, ops::Range};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use opencv::{
+  core::{Mat, Vector},
+  imgcodecs, imgproc,
+  prelude::*,
+};
+
+use crate::dither::Dithering;
+use crate::handler::ImageConvertType;
+
+#[cfg(not(feature = "opencv_old"))]
+use opencv::core::AlgorithmHint;
+
+/// Fallback terminal cell size in pixels (width, height)
+const DEFAULT_CELL_SIZE: (u16, u16) = (8, 16);
+
+/// Kitty image id (reused by every frame, so the image is replaced in place)
+const KITTY_IMAGE_ID: u32 = 1;
+
+/// Kitty placement id
+const KITTY_PLACEMENT_ID: u32 = 1;
+
+/// Maximal size of one Kitty base64 chunk
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// Number of reused Kitty shared memory objects (a frame may still be read by the terminal)
+const KITTY_SHM_SLOTS: u64 = 4;
+
+/// Maximal number of Sixel palette colors
+const SIXEL_PALETTE_SIZE: usize = 256;
+
+/// JPEG quality of iTerm2 inline images
+const ITERM2_JPEG_QUALITY: i32 = 85;
+
+/// Terminal graphics protocol (true-pixel output)
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum GraphicsProtocol {
+  Kitty,
+  Sixel,
+  ITerm2,
+}
+
+impl GraphicsProtocol {
+  /// Detects graphics protocols supported by the terminal (preferred first).
+  ///
+  /// The `TUICAM_GRAPHICS` environment variable overrides the detection
+  /// (a list of `kitty`, `sixel` and `iterm2`, or `none`).
+  pub fn detect() -> Vec<Self> {
+    if let Ok(graphics) = std::env::var("TUICAM_GRAPHICS") {
+      return Self::parse_list(&graphics).unwrap_or_default();
+    }
+
+    let term = std::env::var("TERM").unwrap_or_default();
+    let term_program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+    let lc_terminal = std::env::var("LC_TERMINAL").unwrap_or_default();
+
+    let mut graphics_protocols = Vec::new();
+
+    if std::env::var("KITTY_WINDOW_ID").is_ok()
+      || term.contains("kitty")
+      || term.contains("ghostty")
+      || term_program == "ghostty"
+      || term_program == "WezTerm"
+    {
+      graphics_protocols.push(GraphicsProtocol::Kitty);
+    }
+
+    if term.starts_with("foot")
+      || term.starts_with("mlterm")
+      || term.starts_with("contour")
+      || term_program == "WezTerm"
+    {
+      graphics_protocols.push(GraphicsProtocol::Sixel);
+    }
+
+    if term_program == "iTerm.app"
+      || term_program == "WezTerm"
+      || term_program == "mintty"
+      || lc_terminal == "iTerm2"
+    {
+      graphics_protocols.push(GraphicsProtocol::ITerm2);
+    }
+
+    graphics_protocols
+  }
+
+  /// Finds a protocol by the name (`none` or an unknown name gives None)
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_lowercase().as_str() {
+      "kitty" => Some(GraphicsProtocol::Kitty),
+      "sixel" => Some(GraphicsProtocol::Sixel),
+      "iterm2" => Some(GraphicsProtocol::ITerm2),
+      _ => None,
+    }
+  }
+
+  /// Parses a comma separated list of protocols (`kitty,sixel`, `none` for the text modes only)
+  pub fn parse_list(list: &str) -> Result<Vec<Self>, String> {
+    if list.trim().eq_ignore_ascii_case("none") {
+      return Ok(Vec::new());
+    }
+
+    list
+      .split(',')
+      .map(|name| {
+        Self::from_name(name.trim()).ok_or_else(|| {
+          format!(
+            "Unknown graphics protocol {} (available: kitty, sixel, iterm2, none)",
+            name.trim()
+          )
+        })
+      })
+      .collect()
+  }
+
+  /// Returns a camera mode drawn by the protocol
+  pub fn image_convert_type(&self) -> ImageConvertType {
+    match self {
+      GraphicsProtocol::Kitty => ImageConvertType::Kitty,
+      GraphicsProtocol::Sixel => ImageConvertType::Sixel,
+      GraphicsProtocol::ITerm2 => ImageConvertType::ITerm2,
+    }
+  }
+
+  /// Returns an escape sequence which removes a drawn image.
+  ///
+  /// Sixel and iTerm2 images are removed by redrawing the cells.
+  pub fn clear_sequence(&self) -> Vec<u8> {
+    match self {
+      GraphicsProtocol::Kitty => {
+        format!("\x1b_Ga=d,d=I,i={},q=2\x1b\\", KITTY_IMAGE_ID).into_bytes()
+      }
+      GraphicsProtocol::Sixel | GraphicsProtocol::ITerm2 => Vec::new(),
+    }
+  }
+}
+
+/// Returns a terminal cell size in pixels (width, height)
+pub fn cell_size() -> (u16, u16) {
+  match crossterm::terminal::window_size() {
+    Ok(size) if size.width > 0 && size.height > 0 && size.columns > 0 && size.rows > 0 => {
+      (size.width / size.columns, size.height / size.rows)
+    }
+    _ => DEFAULT_CELL_SIZE,
+  }
+}
+
+/// Converts a BGR frame into a continuous RGB buffer
+fn into_rgb(frame: &Mat) -> opencv::Result<Vec<u8>> {
+  let mut rgb_frame = Mat::default();
+
+  #[cfg(not(feature = "opencv_old"))]
+  imgproc::cvt_color(
+    frame,
+    &mut rgb_frame,
+    imgproc::COLOR_BGR2RGB,
+    0,
+    AlgorithmHint::ALGO_HINT_DEFAULT,
+  )?;
+
+  #[cfg(feature = "opencv_old")]
+  imgproc::cvt_color(frame, &mut rgb_frame, imgproc::COLOR_BGR2RGB, 0)?;
+
+  Ok(rgb_frame.data_bytes()?.to_vec())
+}
+
+/// Kitty graphics protocol encoder.
+///
+/// Transmits raw RGB pixels as chunked base64 data. Shared memory objects are opt-in
+/// (`TUICAM_KITTY_SHM=1`, kitty itself on the same Linux machine only), as other
+/// terminals speaking the protocol may ignore them and never unlink them.
+pub struct KittyEncoder {
+  use_shared_memory: bool,
+  frame_count: u64,
+}
+
+impl KittyEncoder {
+  pub fn new() -> Self {
+    let is_remote = std::env::var("SSH_CONNECTION").is_ok() || std::env::var("SSH_TTY").is_ok();
+    let is_kitty = std::env::var("KITTY_WINDOW_ID").is_ok();
+    let is_requested = std::env::var("TUICAM_KITTY_SHM").is_ok_and(|value| value == "1");
+
+    Self {
+      use_shared_memory: cfg!(target_os = "linux") && is_kitty && is_requested && !is_remote,
+      frame_count: 0,
+    }
+  }
+
+  /// Encodes a BGR frame into a Kitty escape sequence.
+  ///
+  /// The image is scaled by the terminal into `cols` x `rows` cells.
+  pub fn encode(&mut self, frame: &Mat, cols: u16, rows: u16) -> opencv::Result<Vec<u8>> {
+    let rgb = into_rgb(frame)?;
+    let control = format!(
+      "a=T,f=24,s={},v={},i={},p={},c={},r={},C=1,q=2",
+      frame.cols(),
+      frame.rows(),
+      KITTY_IMAGE_ID,
+      KITTY_PLACEMENT_ID,
+      cols,
+      rows
+    );
+
+    self.frame_count += 1;
+
+    if self.use_shared_memory {
+      match self.write_shared_memory(&rgb) {
+        Ok(name) => {
+          return Ok(
+            format!(
+              "\x1b_G{},t=s,S={};{}\x1b\\",
+              control,
+              rgb.len(),
+              STANDARD.encode(name)
+            )
+            .into_bytes(),
+          );
+        }
+        // Shared memory is not available, use the chunked transfer from now on
+        Err(_) => self.use_shared_memory = false,
+      }
+    }
+
+    let data = STANDARD.encode(&rgb);
+    let chunks = data.as_bytes().chunks(KITTY_CHUNK_SIZE).collect::<Vec<_>>();
+    let mut sequence = Vec::with_capacity(data.len() + chunks.len() * 16 + control.len());
+
+    for (i, chunk) in chunks.iter().enumerate() {
+      let more = (i + 1 < chunks.len()) as u8;
+
+      if i == 0 {
+        let _ = write!(sequence, "\x1b_G{},m={};", control, more);
+      } else {
+        let _ = write!(sequence, "\x1b_Gm={};", more);
+      }
+
+      sequence.extend_from_slice(chunk);
+      sequence.extend_from_slice(b"\x1b\\");
+    }
+
+    Ok(sequence)
+  }
+
+  /// Writes the pixels into a POSIX shared memory object.
+  ///
+  /// Returns the object name. The terminal unlinks it after reading, objects it
+  /// skipped are overwritten by later frames and removed on drop.
+  fn write_shared_memory(&self, data: &[u8]) -> std::io::Result<String> {
+    let name = shared_memory_name(self.frame_count % KITTY_SHM_SLOTS);
+    std::fs::write(format!("/dev/shm/{}", name), data)?;
+
+    Ok(name)
+  }
+}
+
+impl Drop for KittyEncoder {
+  fn drop(&mut self) {
+    // All slots, the shared memory may have been turned off after a failed write
+    if cfg!(target_os = "linux") {
+      for slot in 0..KITTY_SHM_SLOTS {
+        let _ = std::fs::remove_file(format!("/dev/shm/{}", shared_memory_name(slot)));
+      }
+    }
+  }
+}
+
+/// Returns a name of the Kitty shared memory object in the slot
+fn shared_memory_name(slot: u64) -> String {
+  format!("tuicam-{}-{}", std::process::id(), slot)
+}
+
+/// Encodes a BGR frame into an iTerm2 inline image escape sequence (`OSC 1337 File=`).
+///
+/// The frame is JPEG encoded and scaled by the terminal into `cols` x `rows` cells.
+pub fn encode_iterm2(frame: &Mat, cols: u16, rows: u16) -> opencv::Result<Vec<u8>> {
+  let mut jpeg = Vector::<u8>::new();
+  let params = Vector::<i32>::from_slice(&[imgcodecs::IMWRITE_JPEG_QUALITY, ITERM2_JPEG_QUALITY]);
+
+  imgcodecs::imencode(".jpg", frame, &mut jpeg, &params)?;
+
+  let sequence = format!(
+    "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=0;doNotMoveCursor=1:{}\x07",
+    jpeg.len(),
+    cols,
+    rows,
+    STANDARD.encode(jpeg.as_slice())
+  );
+
+  Ok(sequence.into_bytes())
+}
+
+/// Box of histogram colors (median cut)
+struct ColorBox {
+  range: Range<usize>,
+  channel: usize,
+  spread: u8,
+}
+
+impl ColorBox {
+  /// Creates a box and finds its widest channel
+  fn new(colors: &[(usize, u32)], range: Range<usize>) -> Self {
+    let (channel, spread) = (0..3)
+      .map(|channel| {
+        let values = colors[range.clone()]
+          .iter()
+          .map(|(key, _)| channel_value(*key, channel));
+        let min = values.clone().min().unwrap_or(0);
+        let max = values.max().unwrap_or(0);
+        (channel, max - min)
+      })
+      .max_by_key(|(_, spread)| *spread)
+      .unwrap_or((0, 0));
+
+    Self {
+      range,
+      channel,
+      spread,
+    }
+  }
+}
+
+/// Returns a 15-bit histogram key of a RGB pixel
+fn color_key(pixel: &[u8]) -> usize {
+  ((pixel[0] as usize >> 3) << 10) | ((pixel[1] as usize >> 3) << 5) | (pixel[2] as usize >> 3)
+}
+
+/// Returns an 8-bit channel value (0 - red, 1 - green, 2 - blue) of a histogram key
+fn channel_value(key: usize, channel: usize) -> u8 {
+  ((((key >> (10 - channel * 5)) & 0x1f) << 3) | 0x04) as u8
+}
+
+/// Reduces RGB pixels into an adaptive palette (median cut).
+///
+/// Returns the palette and a palette index of each pixel.
+pub fn quantize(rgb: &[u8], max_colors: usize) -> (Vec<[u8; 3]>, Vec<u8>) {
+  let max_colors = max_colors.clamp(1, 256);
+
+  let mut histogram = vec![0u32; 1 << 15];
+  for pixel in rgb.chunks_exact(3) {
+    histogram[color_key(pixel)] += 1;
+  }
+
+  let mut colors = histogram
+    .iter()
+    .enumerate()
+    .filter(|(_, count)| **count > 0)
+    .map(|(key, count)| (key, *count))
+    .collect::<Vec<_>>();
+
+  let mut boxes = vec![ColorBox::new(&colors, 0..colors.len())];
+
+  while boxes.len() < max_colors {
+    // Split the box with the widest channel at the median
+    let Some((index, _)) = boxes
+      .iter()
+      .enumerate()
+      .filter(|(_, color_box)| color_box.range.len() > 1)
+      .max_by_key(|(_, color_box)| color_box.spread)
+    else {
+      break;
+    };
+
+    let ColorBox { range, channel, .. } = boxes.swap_remove(index);
+    let slice = &mut colors[range.clone()];
+    slice.sort_unstable_by_key(|(key, _)| channel_value(*key, channel));
+
+    let total = slice.iter().map(|(_, count)| *count as u64).sum::<u64>();
+    let mut count_sum = 0;
+    let mut median = 1;
+
+    for (i, (_, count)) in slice.iter().enumerate() {
+      count_sum += *count as u64;
+      if count_sum * 2 >= total {
+        median = (i + 1).clamp(1, slice.len() - 1);
+        break;
+      }
+    }
+
+    boxes.push(ColorBox::new(&colors, range.start..range.start + median));
+    boxes.push(ColorBox::new(&colors, range.start + median..range.end));
+  }
+
+  let mut lookup = vec![0u8; 1 << 15];
+
+  let palette = boxes
+    .iter()
+    .enumerate()
+    .map(|(i, color_box)| {
+      let mut sum = [0u64; 3];
+      let mut count_sum = 0u64;
+
+      for (key, count) in &colors[color_box.range.clone()] {
+        lookup[*key] = i as u8;
+        count_sum += *count as u64;
+
+        for (channel, channel_sum) in sum.iter_mut().enumerate() {
+          *channel_sum += channel_value(*key, channel) as u64 * *count as u64;
+        }
+      }
+
+      sum.map(|channel_sum| (channel_sum / count_sum.max(1)) as u8)
+    })
+    .collect::<Vec<_>>();
+
+  let indices = rgb
+    .chunks_exact(3)
+    .map(|pixel| lookup[color_key(pixel)])
+    .collect();
+
+  (palette, indices)
+}
+
+/// Encodes a BGR frame into a Sixel escape sequence.
+///
+/// Uses an adaptive palette computed for every frame (optionally dithered).
+pub fn encode_sixel(frame: &Mat, dithering: Dithering) -> opencv::Result<Vec<u8>> {
+  let rgb = into_rgb(frame)?;
+  let (width, height) = (frame.cols() as usize, frame.rows() as usize);
+
+  let (palette, mut indices) = quantize(&rgb, SIXEL_PALETTE_SIZE);
+
+  if dithering != Dithering::None {
+    indices = dithering.dither_palette(&rgb, width, &palette);
+  }
+
+  let mut sequence = Vec::with_capacity(width * height / 2);
+  let _ = write!(sequence, "\x1bP0;1;0q\"1;1;{};{}", width, height);
+
+  for (i, [r, g, b]) in palette.iter().enumerate() {
+    let _ = write!(
+      sequence,
+      "#{};2;{};{};{}",
+      i,
+      *r as u32 * 100 / 255,
+      *g as u32 * 100 / 255,
+      *b as u32 * 100 / 255
+    );
+  }
+
+  // Sixel bits of each palette color in the current band
+  let mut band = vec![0u8; palette.len() * width];
+  let mut is_used = vec![false; palette.len()];
+
+  for band_y in (0..height).step_by(6) {
+    for dy in 0..6.min(height - band_y) {
+      for x in 0..width {
+        let color = indices[(band_y + dy) * width + x] as usize;
+        band[color * width + x] |= 1 << dy;
+        is_used[color] = true;
+      }
+    }
+
+    for color in 0..palette.len() {
+      if !is_used[color] {
+        continue;
+      }
+
+      let _ = write!(sequence, "#{}", color);
+
+      let row = &mut band[color * width..(color + 1) * width];
+      let mut x = 0;
+
+      while x < width {
+        let bits = row[x];
+        let run = row[x..].iter().take_while(|other| **other == bits).count();
+        let sixel = 63 + bits;
+
+        if run > 3 {
+          let _ = write!(sequence, "!{}{}", run, sixel as char);
+        } else {
+          sequence.extend(std::iter::repeat_n(sixel, run));
+        }
+
+        x += run;
+      }
+
+      sequence.push(b'$');
+      row.fill(0);
+      is_used[color] = false;
+    }
+
+    sequence.push(b'-');
+  }
+
+  sequence.extend_from_slice(b"\x1b\\");
+
+  Ok(sequence)
+}
@@ -1,9 +1,14 @@
-use std::{sync::Arc, time::Duration};
+use std::{
+  collections::{BTreeMap, HashMap},
+  path::PathBuf,
+  sync::Arc,
+  time::{Duration, Instant},
+};
 
 use crossterm::event::{Event, EventStream};
 use futures::{FutureExt, StreamExt};
 use ratatui::{
-  layout::Size,
+  layout::{Rect, Size},
   style::{Color, Style},
   text::{Line, Span, Text},
 };
@@ -13,14 +18,27 @@ use tokio::sync::RwLock;
 use opencv::{
   imgproc,
   prelude::*,
-  videoio::{self, VideoCapture, VideoCaptureTrait},
+  videoio::{self, VideoCapture},
 };
 
 #[cfg(not(feature = "opencv_old"))]
 use opencv::core::AlgorithmHint;
 
+use crate::adjust::ImageAdjustments;
 use crate::app::ASCII_CHARS;
 use crate::channel::AppEvent;
+use crate::cli::Options;
+use crate::controls::{CameraControls, CameraPreset};
+use crate::device::{self, CameraDevice, CaptureMode};
+use crate::dither::Dithering;
+use crate::error::TuicamError;
+use crate::graphics::{self, GraphicsProtocol, KittyEncoder};
+use crate::pattern::TestPattern;
+use crate::ramp::AsciiRamp;
+use crate::recorder::{RecordOptions, VideoRecorder};
+use crate::snapshot::{self, SnapshotFormat};
+use crate::source::FrameSource;
+use crate::threshold::Threshold;
 
 type TerminalSize = (u16, u16);
 
@@ -32,61 +50,368 @@ pub enum ImageConvertType {
   GrayScale,
   GrayScaleThreshold,
   Threshold,
+  Ascii,
+  Braille,
+  ColorfulBraille,
+  Sextant,
+  Octant,
+  Kitty,
+  Sixel,
+  ITerm2,
+}
+
+impl ImageConvertType {
+  /// All modes in the switch order
+  pub const ALL: [ImageConvertType; 13] = [
+    ImageConvertType::ColorfulHalfBlock,
+    ImageConvertType::Colorful,
+    ImageConvertType::GrayScale,
+    ImageConvertType::GrayScaleThreshold,
+    ImageConvertType::Threshold,
+    ImageConvertType::Ascii,
+    ImageConvertType::Braille,
+    ImageConvertType::ColorfulBraille,
+    ImageConvertType::Sextant,
+    ImageConvertType::Octant,
+    ImageConvertType::Kitty,
+    ImageConvertType::Sixel,
+    ImageConvertType::ITerm2,
+  ];
+
+  /// Returns a mode name (used by the CLI)
+  pub fn name(&self) -> &'static str {
+    match self {
+      ImageConvertType::ColorfulHalfBlock => "halfblock",
+      ImageConvertType::Colorful => "colorful",
+      ImageConvertType::GrayScale => "grayscale",
+      ImageConvertType::GrayScaleThreshold => "grayscale-threshold",
+      ImageConvertType::Threshold => "threshold",
+      ImageConvertType::Ascii => "ascii",
+      ImageConvertType::Braille => "braille",
+      ImageConvertType::ColorfulBraille => "colorful-braille",
+      ImageConvertType::Sextant => "sextant",
+      ImageConvertType::Octant => "octant",
+      ImageConvertType::Kitty => "kitty",
+      ImageConvertType::Sixel => "sixel",
+      ImageConvertType::ITerm2 => "iterm2",
+    }
+  }
+
+  /// Finds a mode by the name
+  pub fn from_name(name: &str) -> Option<Self> {
+    let name = name.to_lowercase();
+    Self::ALL.into_iter().find(|mode| mode.name() == name)
+  }
+
+  /// Checks if the mode reduces tones (or colors), so it can be dithered
+  pub fn is_ditherable(&self) -> bool {
+    self.is_threshold()
+      || matches!(
+        self,
+        ImageConvertType::GrayScaleThreshold | ImageConvertType::Sixel
+      )
+  }
+
+  /// Checks if the mode draws a binary frame (of the configured threshold)
+  pub fn is_threshold(&self) -> bool {
+    matches!(
+      self,
+      ImageConvertType::Threshold | ImageConvertType::Braille
+    )
+  }
+
+  /// Returns a graphics protocol drawing the mode (None for text modes)
+  pub fn graphics_protocol(&self) -> Option<GraphicsProtocol> {
+    match self {
+      ImageConvertType::Kitty => Some(GraphicsProtocol::Kitty),
+      ImageConvertType::Sixel => Some(GraphicsProtocol::Sixel),
+      ImageConvertType::ITerm2 => Some(GraphicsProtocol::ITerm2),
+      _ => None,
+    }
+  }
 }
 
 /// Camera window frame scale
-#[derive(Clone)]
+#[derive(PartialEq, Clone)]
 pub enum CamWindowScale {
   Full = 1,
   Small = 2,
 }
 
+impl CamWindowScale {
+  /// Returns a scale name (used by the CLI and the config file)
+  pub fn name(&self) -> &'static str {
+    match self {
+      CamWindowScale::Small => "small",
+      CamWindowScale::Full => "full",
+    }
+  }
+
+  /// Finds a scale by the name (`small` or `full`)
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_lowercase().as_str() {
+      "small" => Some(CamWindowScale::Small),
+      "full" => Some(CamWindowScale::Full),
+      _ => None,
+    }
+  }
+}
+
+/// Delay between two rescans of the device cameras
+const CAMERA_RESCAN_INTERVAL: Duration = Duration::from_secs(3);
+
 /// Camera contains all available device cameras
 pub struct Camera {
-  pub(crate) active_index: Option<i32>,
-  pub(crate) ids: Vec<i32>,
+  /// Active camera id (kept while the camera is disconnected, so it can reconnect)
+  pub(crate) active_id: Option<i32>,
+  pub(crate) devices: Vec<CameraDevice>,
+
+  /// Capture modes set at runtime (by the camera id), other cameras pick one automatically
+  pub(crate) capture_modes: HashMap<i32, CaptureMode>,
 }
 
 impl Camera {
   pub fn default() -> Self {
-    let ids = (0..=10)
-      .filter_map(|id| {
-        if let Ok(cam) = VideoCapture::new(id, videoio::CAP_ANY) {
-          if cam.is_opened().unwrap_or(false) {
-            return Some(id);
-          } else {
-            return None;
-          }
-        }
-
-        None
-      })
-      .collect::<Vec<i32>>();
+    let devices = device::enumerate(&[], None);
 
     Self {
-      active_index: Some(0),
-      ids,
+      active_id: devices.first().map(|device| device.id),
+      devices,
+      capture_modes: HashMap::new(),
     }
   }
 
   /// Switches a current camera
   pub fn switch(&mut self) {
-    if let Some(active_index) = self.active_index.as_mut() {
-      if self.ids.len() > (*active_index + 1) as usize {
-        *active_index += 1;
-      } else {
-        *active_index = 0;
-      }
+    let next_index = self
+      .get_cam_id()
+      .and_then(|cam_id| self.devices.iter().position(|device| device.id == *cam_id))
+      .map_or(0, |index| index + 1);
+
+    self.active_id = self
+      .devices
+      .get(next_index)
+      .or(self.devices.first())
+      .map(|device| device.id);
+  }
+
+  /// Creates a camera list without probing any device
+  pub fn none() -> Self {
+    Self {
+      active_id: None,
+      devices: Vec::new(),
+      capture_modes: HashMap::new(),
+    }
+  }
+
+  /// Checks if the active camera is connected
+  pub fn is_connected(&self) -> bool {
+    self.active_device().is_some()
+  }
+
+  /// Returns the active camera (None if disconnected)
+  pub fn active_device(&self) -> Option<&CameraDevice> {
+    let active_id = self.active_id?;
+    self.devices.iter().find(|device| device.id == active_id)
+  }
+
+  /// Replaces the available cameras, returns the connected and the disconnected ones.
+  ///
+  /// The active camera stays active even if it's disconnected.
+  pub fn update_devices(
+    &mut self,
+    devices: Vec<CameraDevice>,
+  ) -> (Vec<CameraDevice>, Vec<CameraDevice>) {
+    let is_same = |a: &CameraDevice, b: &CameraDevice| a.id == b.id && a.name == b.name;
+
+    let connected = devices
+      .iter()
+      .filter(|device| !self.devices.iter().any(|known| is_same(known, device)))
+      .cloned()
+      .collect();
+    let disconnected = self
+      .devices
+      .iter()
+      .filter(|known| !devices.iter().any(|device| is_same(known, device)))
+      .cloned()
+      .collect();
+
+    self.devices = devices;
+
+    if self.active_id.is_none() {
+      self.active_id = self.devices.first().map(|device| device.id);
+    }
+
+    (connected, disconnected)
+  }
+
+  /// Finds an id of the camera given by the selector
+  pub fn find(&self, selector: &CameraSelector) -> Option<i32> {
+    self
+      .devices
+      .iter()
+      .find(|device| selector.matches(device))
+      .map(|device| device.id)
+  }
+
+  /// Makes the camera active (if available)
+  pub fn select(&mut self, id: i32) {
+    if self.devices.iter().any(|device| device.id == id) {
+      self.active_id = Some(id);
     }
   }
 
   /// Return a current active camera id
   pub fn get_cam_id(&self) -> Option<&i32> {
-    if let Some(active_index) = self.active_index.as_ref() {
-      self.ids.get(*active_index as usize)
-    } else {
-      None
+    self.active_id.as_ref()
+  }
+
+  /// Switches a capture mode of the active camera.
+  ///
+  /// Cycles through the automatic mode and all modes supported by the camera.
+  /// Returns the new mode (None for the automatic one).
+  pub fn switch_capture_mode(&mut self) -> Option<CaptureMode> {
+    let device = self.active_device()?;
+    let id = device.id;
+    let modes = device.capture_modes();
+
+    let next_mode = match self.capture_modes.get(&id) {
+      Some(mode) => modes
+        .iter()
+        .position(|known_mode| known_mode == mode)
+        .and_then(|index| modes.get(index + 1)),
+      None => modes.first(),
     }
+    .cloned();
+
+    match next_mode.as_ref() {
+      Some(mode) => self.capture_modes.insert(id, mode.clone()),
+      None => self.capture_modes.remove(&id),
+    };
+
+    next_mode
+  }
+}
+
+/// Device camera given on the command line
+pub enum CameraSelector {
+  Index(i32),
+  Name(String),
+
+  // Device node or a persistent link (`/dev/v4l/by-id/...`)
+  Path(PathBuf),
+}
+
+impl CameraSelector {
+  /// Parses a camera index, a device path or a name
+  pub fn parse(value: &str) -> Self {
+    if value.starts_with('/') {
+      return CameraSelector::Path(PathBuf::from(value));
+    }
+
+    match value.parse() {
+      Ok(id) => CameraSelector::Index(id),
+      Err(_) => CameraSelector::Name(value.to_string()),
+    }
+  }
+
+  /// Checks if the selector matches the camera
+  pub fn matches(&self, device: &CameraDevice) -> bool {
+    match self {
+      CameraSelector::Index(id) => device.id == *id,
+      CameraSelector::Name(name) => device.matches_name(name),
+      CameraSelector::Path(path) => device.matches_path(path),
+    }
+  }
+
+  /// Returns a selector which survives reboots (a persistent link or a name)
+  pub fn stable(device: &CameraDevice) -> Self {
+    match device.stable_path.as_ref() {
+      Some(stable_path) => CameraSelector::Path(stable_path.clone()),
+      None => CameraSelector::Name(device.name.clone()),
+    }
+  }
+}
+
+impl std::fmt::Display for CameraSelector {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    match self {
+      CameraSelector::Index(id) => write!(f, "{}", id),
+      CameraSelector::Name(name) => write!(f, "{}", name),
+      CameraSelector::Path(path) => write!(f, "{}", path.display()),
+    }
+  }
+}
+
+/// Available playback speeds of a video file
+const PLAYBACK_SPEEDS: &[f64] = &[0.25, 0.5, 1.0, 1.5, 2.0, 4.0];
+
+/// Seek step of a video file in milliseconds
+const SEEK_STEP_MS: f64 = 5000.0;
+
+/// Video file playback state
+pub struct Playback {
+  /// Playback is paused
+  pub is_paused: bool,
+
+  /// Starts again from the beginning when the end of the file is reached
+  pub is_looping: bool,
+
+  /// Index into the `PLAYBACK_SPEEDS`
+  speed_index: usize,
+
+  /// Pending seek offset in milliseconds
+  seek_offset_ms: f64,
+}
+
+impl Playback {
+  pub fn default() -> Self {
+    Self {
+      is_paused: false,
+      is_looping: true,
+      speed_index: 2,
+      seek_offset_ms: 0.0,
+    }
+  }
+
+  /// Returns a current playback speed multiplier
+  pub fn speed(&self) -> f64 {
+    PLAYBACK_SPEEDS[self.speed_index]
+  }
+
+  /// Toggles play/pause
+  pub fn toggle_pause(&mut self) {
+    self.is_paused = !self.is_paused;
+  }
+
+  /// Toggles looping
+  pub fn toggle_loop(&mut self) {
+    self.is_looping = !self.is_looping;
+  }
+
+  /// Increases a playback speed
+  pub fn speed_up(&mut self) {
+    self.speed_index = (self.speed_index + 1).min(PLAYBACK_SPEEDS.len() - 1);
+  }
+
+  /// Decreases a playback speed
+  pub fn slow_down(&mut self) {
+    self.speed_index = self.speed_index.saturating_sub(1);
+  }
+
+  /// Seeks forward by the seek step
+  pub fn seek_forward(&mut self) {
+    self.seek_offset_ms += SEEK_STEP_MS;
+  }
+
+  /// Seeks backward by the seek step
+  pub fn seek_backward(&mut self) {
+    self.seek_offset_ms -= SEEK_STEP_MS;
+  }
+
+  /// Takes a pending seek offset
+  pub fn take_seek_offset(&mut self) -> f64 {
+    std::mem::take(&mut self.seek_offset_ms)
   }
 }
 
@@ -105,23 +430,361 @@ pub struct FrameHandlerConfig {
   pub camera: Camera,
 
   /// Event handlers are locked
-  pub is_locked: bool
+  pub is_locked: bool,
+
+  /// Video file used instead of a device camera
+  pub input: Option<PathBuf>,
+
+  /// Video file playback state
+  pub playback: Playback,
+
+  /// Description of the active frame source
+  pub source_description: String,
+
+  /// Active test pattern (used instead of a device camera)
+  pub pattern: Option<TestPattern>,
+
+  /// Graphics protocols supported by the terminal
+  pub graphics_protocols: Vec<GraphicsProtocol>,
+
+  /// Inner area of the camera block (set by the app after each draw)
+  pub cam_area: Rect,
+
+  /// Character ramp of the ASCII art mode
+  pub ascii_ramp: AsciiRamp,
+
+  /// Directory of saved snapshots
+  pub snapshot_dir: PathBuf,
+
+  /// Image format of saved snapshots
+  pub snapshot_format: SnapshotFormat,
+
+  /// Snapshot of the next frame is requested (reset by the frame handler)
+  pub is_snapshot_requested: bool,
+
+  /// The last frame is converted again even if the source has no new one,
+  /// e.g. a paused video after a settings change (reset by the frame handler)
+  pub is_redraw_requested: bool,
+
+  /// Video recording options
+  pub record_options: RecordOptions,
+
+  /// Video recording is requested (reset by the frame handler if the recording fails)
+  pub is_recording: bool,
+
+  /// Start time of the active video recording (set by the frame handler)
+  pub recording_started_at: Option<Instant>,
+
+  /// Delay between two frames of a device camera or a test pattern
+  pub frame_interval: Duration,
+
+  /// Requested capture resolution of a device camera (width, height)
+  pub resolution: Option<(i32, i32)>,
+
+  /// Requested pixel format of a device camera (FourCC)
+  pub fourcc: Option<String>,
+
+  /// Image is mirrored horizontally
+  pub is_mirrored: bool,
+
+  /// Binary threshold of the threshold modes
+  pub threshold: Threshold,
+
+  /// Dithering of the reduced tone modes (replaces the threshold) and the Sixel palette
+  pub dithering: Dithering,
+
+  /// Frame source should be reopened after a failure (reset by the frame handler)
+  pub is_retry_requested: bool,
+
+  /// Frame source failed and is released until a retry (set by the frame handler)
+  pub is_source_failed: bool,
+
+  /// App is exiting, the frame handler finishes a recording and ends
+  pub is_exit_requested: bool,
+
+  /// Control values of the active camera (applied by the frame source)
+  pub camera_controls: CameraControls,
+
+  /// Camera control presets by the camera selector (applied when the camera is opened)
+  pub camera_presets: BTreeMap<String, CameraPreset>,
+
+  /// Software adjustments of the resized frame
+  pub image_adjustments: ImageAdjustments,
 }
 
 impl FrameHandlerConfig {
-  pub fn new(terminal_size: Size) -> Self {
-    Self {
-      image_convert_type: ImageConvertType::ColorfulHalfBlock,
+  /// Creates a config of the startup options.
+  ///
+  /// Fails if the requested camera is not available.
+  pub fn try_new(terminal_size: Size, options: Options) -> Result<Self, Box<dyn std::error::Error>> {
+    let mut camera = if options.input.is_some() {
+      Camera::none()
+    } else {
+      Camera::default()
+    };
+
+    if let Some(id) = options
+      .preferred_camera
+      .as_ref()
+      .and_then(|selector| camera.find(selector))
+    {
+      camera.select(id);
+    }
+
+    if let Some(selector) = options.camera.as_ref() {
+      let id = camera
+        .find(selector)
+        .ok_or_else(|| format!("Camera {} is not available (see --list-cameras)", selector))?;
+
+      camera.select(id);
+    }
+
+    // Without any camera fall back to a test pattern
+    let pattern = match options.pattern {
+      None if options.input.is_none() && camera.devices.is_empty() => Some(TestPattern::ColorBars),
+      pattern => pattern,
+    };
+
+    let mut ascii_ramp = AsciiRamp::new(options.custom_ramp);
+    ascii_ramp.set_kind(options.ramp_kind);
+
+    let graphics_protocols = options
+      .graphics_protocols
+      .unwrap_or_else(GraphicsProtocol::detect);
+
+    // A graphics mode falls back to a text mode in a terminal without the protocol
+    let image_convert_type = match options.image_convert_type.graphics_protocol() {
+      Some(graphics_protocol) if !graphics_protocols.contains(&graphics_protocol) => {
+        ImageConvertType::ColorfulHalfBlock
+      }
+      _ => options.image_convert_type,
+    };
+
+    Ok(Self {
+      image_convert_type,
       terminal_size: (terminal_size.width, terminal_size.height),
-      cam_window_scale: CamWindowScale::Small,
-      camera: Camera::default(),
-      is_locked: false
+      cam_window_scale: options.cam_window_scale,
+      camera,
+      is_locked: false,
+      input: options.input,
+      playback: Playback::default(),
+      source_description: String::new(),
+      pattern,
+      graphics_protocols,
+      cam_area: Rect::default(),
+      ascii_ramp,
+      snapshot_dir: options.snapshot_dir.unwrap_or_else(snapshot::default_dir),
+      snapshot_format: options.snapshot_format,
+      is_snapshot_requested: false,
+      is_redraw_requested: false,
+      record_options: options.record_options,
+      is_recording: false,
+      recording_started_at: None,
+      frame_interval: options.frame_interval,
+      resolution: options.resolution,
+      fourcc: options.fourcc,
+      is_mirrored: options.is_mirrored,
+      threshold: Threshold::new(
+        options.threshold_method,
+        options.threshold,
+        options.block_size,
+      ),
+      dithering: options.dithering,
+      is_retry_requested: false,
+      is_source_failed: false,
+      is_exit_requested: false,
+      camera_controls: CameraControls::default(),
+      camera_presets: options.camera_presets,
+      image_adjustments: ImageAdjustments::default(),
+    })
+  }
+
+  /// Returns a size of the converted frame in pixels (the frame is resized to it)
+  pub fn target_size(&self) -> opencv::core::Size {
+    let cam_size = opencv::core::Size {
+      width: (self.terminal_size.0 / self.cam_window_scale.clone() as u16) as i32,
+      height: (self.terminal_size.1 / self.cam_window_scale.clone() as u16) as i32,
+    };
+
+    match self.image_convert_type {
+      ImageConvertType::ColorfulHalfBlock => opencv::core::Size {
+        width: cam_size.width * 2,
+        height: cam_size.height * 2,
+      },
+      ImageConvertType::Braille | ImageConvertType::ColorfulBraille | ImageConvertType::Octant => {
+        opencv::core::Size {
+          width: cam_size.width * 2,
+          height: cam_size.height * 4,
+        }
+      }
+      ImageConvertType::Sextant => opencv::core::Size {
+        width: cam_size.width * 2,
+        height: cam_size.height * 3,
+      },
+      ImageConvertType::Kitty | ImageConvertType::ITerm2 => {
+        let (cell_width, cell_height) = graphics::cell_size();
+        let (cam_width, cam_height) = self.cam_area_size(cam_size);
+
+        opencv::core::Size {
+          width: cam_width * cell_width as i32,
+          height: cam_height * cell_height as i32,
+        }
+      }
+      // Sixel is not scaled, the height is rounded down to whole sixel bands
+      ImageConvertType::Sixel => {
+        let (cell_width, cell_height) = graphics::cell_size();
+        let (cam_width, cam_height) = self.cam_area_size(cam_size);
+
+        opencv::core::Size {
+          width: cam_width * cell_width as i32,
+          height: cam_height * cell_height as i32 / 6 * 6,
+        }
+      }
+      _ => cam_size,
+    }
+  }
+
+  /// Returns a size of the camera area in cells.
+  ///
+  /// The area is known after the first draw, until then it's estimated of the window size.
+  fn cam_area_size(&self, cam_size: opencv::core::Size) -> (i32, i32) {
+    if self.cam_area.is_empty() {
+      // Without the window border
+      ((cam_size.width - 2).max(1), (cam_size.height - 2).max(1))
+    } else {
+      (self.cam_area.width as i32, self.cam_area.height as i32)
+    }
+  }
+
+  /// Returns a capture mode set at runtime for the active camera
+  fn runtime_capture_mode(&self) -> Option<&CaptureMode> {
+    self.camera.capture_modes.get(self.camera.get_cam_id()?)
+  }
+
+  /// Returns a requested capture mode of the active camera.
+  ///
+  /// A mode set at runtime, otherwise the requested resolution, pixel format and frame rate
+  /// (no resolution for the automatic mode). The camera is reopened when it changes.
+  pub fn requested_capture_mode(&self) -> Option<CaptureMode> {
+    self.camera.get_cam_id()?;
+
+    if let Some(capture_mode) = self.runtime_capture_mode() {
+      return Some(capture_mode.clone());
+    }
+
+    Some(CaptureMode {
+      fourcc: self.fourcc.clone(),
+      resolution: self
+        .resolution
+        .map(|(width, height)| (width as u32, height as u32)),
+      fps: Some(1.0 / self.frame_interval.as_secs_f64()),
+    })
+  }
+
+  /// Returns a capture mode of the active camera.
+  ///
+  /// A mode set at runtime wins, otherwise the smallest mode covering the requested
+  /// resolution (or the target size of the camera mode and scale) is picked.
+  /// Without known capabilities only the requested values are set.
+  pub fn capture_mode(&self) -> Option<CaptureMode> {
+    let requested_mode = self.requested_capture_mode()?;
+
+    if self.runtime_capture_mode().is_some() {
+      return Some(requested_mode);
+    }
+
+    let target = requested_mode.resolution.unwrap_or_else(|| {
+      let target_size = self.target_size();
+      (
+        target_size.width.max(0) as u32,
+        target_size.height.max(0) as u32,
+      )
+    });
+
+    let fps = requested_mode.fps.unwrap_or_default();
+
+    let best_capture_mode = self
+      .camera
+      .active_device()
+      .and_then(|device| device.best_capture_mode(target, requested_mode.fourcc.as_deref(), fps));
+
+    best_capture_mode.or_else(|| {
+      (requested_mode.resolution.is_some() || requested_mode.fourcc.is_some()).then(|| {
+        CaptureMode {
+          fps: None,
+          ..requested_mode
+        }
+      })
+    })
+  }
+
+  /// Returns a control preset of the active camera
+  pub fn camera_preset(&self) -> Option<&CameraPreset> {
+    let device = self.camera.active_device()?;
+
+    self
+      .camera_presets
+      .iter()
+      .find(|(selector, _)| CameraSelector::parse(selector).matches(device))
+      .map(|(_, preset)| preset)
+  }
+
+  /// Stores current control values as a preset of the active camera.
+  ///
+  /// The preset is stored by a selector which survives reboots, returns the camera name.
+  pub fn save_camera_preset(&mut self) -> Option<String> {
+    let device = self.camera.active_device()?;
+    let selector = CameraSelector::stable(device).to_string();
+
+    // An older preset may use an another selector of the same camera
+    self
+      .camera_presets
+      .retain(|preset_selector, _| !CameraSelector::parse(preset_selector).matches(device));
+    self
+      .camera_presets
+      .insert(selector, self.camera_controls.preset());
+
+    Some(device.name.clone())
+  }
+
+  /// Switches an input.
+  ///
+  /// Cycles through device cameras and then through test patterns.
+  pub fn switch_input(&mut self) {
+    if self.input.is_some() {
+      return;
+    }
+
+    match self.pattern {
+      Some(pattern) => {
+        self.pattern = pattern.next();
+
+        if self.pattern.is_none() {
+          match self.camera.devices.first() {
+            Some(device) => self.camera.active_id = Some(device.id),
+            None => self.pattern = Some(TestPattern::ColorBars),
+          }
+        }
+      }
+      None => {
+        let last_id = self.camera.devices.last().map(|device| device.id);
+        let is_last = last_id.is_none() || self.camera.get_cam_id() == last_id.as_ref();
+
+        if is_last {
+          self.pattern = Some(TestPattern::ColorBars);
+        } else {
+          self.camera.switch();
+        }
+      }
     }
   }
 }
 
 /// Converts a frame into a grayscale.
-fn convert_into_grayscale(frame: &opencv::core::Mat, res_frame: &mut opencv::core::Mat) {
+pub(crate) fn convert_into_grayscale(
+  frame: &opencv::core::Mat,
+  res_frame: &mut opencv::core::Mat,
+) -> opencv::Result<()> {
   #[cfg(not(feature = "opencv_old"))]
   {
     imgproc::cvt_color(
@@ -131,12 +794,62 @@ fn convert_into_grayscale(frame: &opencv::core::Mat, res_frame: &mut opencv::cor
       0,
       AlgorithmHint::ALGO_HINT_DEFAULT,
     )
-    .unwrap();
   }
 
   #[cfg(feature = "opencv_old")]
   {
-    imgproc::cvt_color(frame, res_frame, imgproc::COLOR_BGR2GRAY, 0).unwrap();
+    imgproc::cvt_color(frame, res_frame, imgproc::COLOR_BGR2GRAY, 0)
+  }
+}
+
+/// Converts a resized frame into the color space of the mode (BGR, grayscale or binary)
+fn convert_frame(
+  frame: &opencv::core::Mat,
+  config: &FrameHandlerConfig,
+) -> opencv::Result<opencv::core::Mat> {
+  match config.image_convert_type {
+    ImageConvertType::Colorful
+    | ImageConvertType::ColorfulHalfBlock
+    | ImageConvertType::ColorfulBraille
+    | ImageConvertType::Sextant
+    | ImageConvertType::Octant
+    | ImageConvertType::Kitty
+    | ImageConvertType::Sixel
+    | ImageConvertType::ITerm2 => Ok(frame.clone()),
+    ImageConvertType::GrayScale | ImageConvertType::Ascii => {
+      let mut gray_frame = opencv::core::Mat::default();
+      convert_into_grayscale(frame, &mut gray_frame)?;
+      Ok(gray_frame)
+    }
+    ImageConvertType::GrayScaleThreshold => {
+      let mut gray_frame = opencv::core::Mat::default();
+      convert_into_grayscale(frame, &mut gray_frame)?;
+
+      // Without dithering the tones are rounded to the glyphs later
+      if config.dithering != Dithering::None {
+        config
+          .dithering
+          .dither_frame(&mut gray_frame, ASCII_CHARS.len())?;
+      }
+
+      Ok(gray_frame)
+    }
+    ImageConvertType::Threshold | ImageConvertType::Braille => {
+      let mut gray_frame = opencv::core::Mat::default();
+      let mut binary_frame = opencv::core::Mat::default();
+
+      convert_into_grayscale(frame, &mut gray_frame)?;
+
+      // Dithering spreads the tones over black and white pixels instead of a threshold
+      if config.dithering != Dithering::None {
+        config.dithering.dither_frame(&mut gray_frame, 2)?;
+        return Ok(gray_frame);
+      }
+
+      config.threshold.apply(&gray_frame, &mut binary_frame)?;
+
+      Ok(binary_frame)
+    }
   }
 }
 
@@ -148,28 +861,172 @@ fn color_dist(lhs: &[u8; 3], rhs: &[u8; 3]) -> u32 {
   x * x + y * y + z * z
 }
 
-/// Computes the distance between two colors
-fn color_average<const N: usize>(colors: [[u8; 3]; N]) -> [u8; 3] {
-  let x = colors.iter().map(|color| color[0] as u32).sum::<u32>() / (N as u32);
-  let y = colors.iter().map(|color| color[1] as u32).sum::<u32>() / (N as u32);
-  let z = colors.iter().map(|color| color[2] as u32).sum::<u32>() / (N as u32);
+/// Computes the average of colors
+fn color_average(colors: &[[u8; 3]]) -> [u8; 3] {
+  let n = colors.len().max(1) as u32;
+  let x = colors.iter().map(|color| color[0] as u32).sum::<u32>() / n;
+  let y = colors.iter().map(|color| color[1] as u32).sum::<u32>() / n;
+  let z = colors.iter().map(|color| color[2] as u32).sum::<u32>() / n;
   [x as u8, y as u8, z as u8]
 }
 
+/// Computes the perceived brightness of a BGR color
+fn color_luminance(color: &[u8; 3]) -> u32 {
+  (color[2] as u32 * 299 + color[1] as u32 * 587 + color[0] as u32 * 114) / 1000
+}
+
+/// Braille dot positions (x, y) of the dot bits
+///
+///  0 | 3
+///  1 | 4
+///  2 | 5
+///  6 | 7
+pub(crate) const BRAILLE_DOTS: [(i32, i32); 8] = [
+  (0, 0),
+  (0, 1),
+  (0, 2),
+  (1, 0),
+  (1, 1),
+  (1, 2),
+  (0, 3),
+  (1, 3),
+];
+
+/// Minimal luminance spread of a cell to draw the colorful braille dots separately
+const BRAILLE_MIN_CONTRAST: u32 = 24;
+
+/// Returns a braille glyph of the dot bits
+fn braille_char(bits: u8) -> char {
+  char::from_u32(0x2800 + bits as u32).unwrap_or(' ')
+}
+
+/// Returns a sextant (2x3) glyph of the subpixel bits (row by row, left to right)
+pub(crate) fn sextant_char(bits: u8) -> char {
+  match bits {
+    0 => ' ',
+    0b010101 => '▌',
+    0b101010 => '▐',
+    0b111111 => '█',
+    // The sextant block skips the empty, the full and both half patterns
+    _ => {
+      let index = bits as u32 - 1 - (bits > 0b010101) as u32 - (bits > 0b101010) as u32;
+      char::from_u32(0x1FB00 + index).unwrap_or(' ')
+    }
+  }
+}
+
+/// Octant (2x4) patterns which are encoded outside of the octant block
+const OCTANT_EXCEPTIONS: [(u8, char); 26] = [
+  (0x00, ' '),
+  (0x01, '\u{1CEA8}'),
+  (0x02, '\u{1CEAB}'),
+  (0x03, '\u{1FB82}'),
+  (0x05, '▘'),
+  (0x0A, '▝'),
+  (0x0F, '▀'),
+  (0x14, '\u{1FBE6}'),
+  (0x28, '\u{1FBE7}'),
+  (0x3F, '\u{1FB85}'),
+  (0x40, '\u{1CEA3}'),
+  (0x50, '▖'),
+  (0x55, '▌'),
+  (0x5A, '▞'),
+  (0x5F, '▛'),
+  (0x80, '\u{1CEA0}'),
+  (0xA0, '▗'),
+  (0xA5, '▚'),
+  (0xAA, '▐'),
+  (0xAF, '▜'),
+  (0xC0, '▂'),
+  (0xF0, '▄'),
+  (0xF5, '▙'),
+  (0xFA, '▟'),
+  (0xFC, '▆'),
+  (0xFF, '█'),
+];
+
+/// Returns an octant (2x4) glyph of the subpixel bits (row by row, left to right)
+pub(crate) fn octant_char(bits: u8) -> char {
+  if let Some((_, glyph)) = OCTANT_EXCEPTIONS.iter().find(|(pattern, _)| *pattern == bits) {
+    return *glyph;
+  }
+
+  let skipped = OCTANT_EXCEPTIONS
+    .iter()
+    .filter(|(pattern, _)| *pattern < bits)
+    .count() as u32;
+
+  char::from_u32(0x1CD00 + bits as u32 - skipped).unwrap_or(' ')
+}
+
+/// Splits subpixels into two color groups (foreground and background).
+///
+/// The groups are seeded with the two most distant subpixels and refined once.
+/// Returns the foreground bits and the foreground and background colors.
+fn cluster_subpixels(subpixels: &[[u8; 3]]) -> (u8, [u8; 3], [u8; 3]) {
+  let mut seeds = (0, 0);
+  let mut max_dist = 0;
+
+  for i in 0..subpixels.len() {
+    for j in (i + 1)..subpixels.len() {
+      let dist = color_dist(&subpixels[i], &subpixels[j]);
+
+      if dist > max_dist {
+        max_dist = dist;
+        seeds = (i, j);
+      }
+    }
+  }
+
+  let group_average = |bits: u8, is_foreground: bool| {
+    let colors = subpixels
+      .iter()
+      .enumerate()
+      .filter(|(i, _)| (bits & (1 << i) != 0) == is_foreground)
+      .map(|(_, color)| *color)
+      .collect::<Vec<_>>();
+
+    color_average(&colors)
+  };
+
+  let (mut fg_color, mut bg_color) = (subpixels[seeds.0], subpixels[seeds.1]);
+  let mut bits = 0;
+
+  for _ in 0..2 {
+    bits = subpixels
+      .iter()
+      .enumerate()
+      .filter(|(_, color)| color_dist(color, &fg_color) <= color_dist(color, &bg_color))
+      .fold(0, |bits, (i, _)| bits | 1 << i);
+
+    fg_color = group_average(bits, true);
+    bg_color = group_average(bits, false);
+  }
+
+  (bits, fg_color, bg_color)
+}
+
 /// Converts a camera frame into ASCII frame.
 ///
 /// This method resizes the frame to a smaller size and then converts each pixel
 /// into an ASCII character based on its intensity. The intensity is calculated
 /// from the pixel's RGB values (Colorful), and the corresponding ASCII character is inserted
 /// based on that intensity.
+///
+/// Fails if the frame doesn't match the mode (e.g. a color frame in a grayscale mode).
 pub fn convert_frame_into_ascii(
   frame: opencv::core::Mat,
   image_convert_type: ImageConvertType,
-) -> Text<'static> {
+  ascii_ramp: &AsciiRamp,
+) -> opencv::Result<Text<'static>> {
   let mut lines = Vec::new();
 
   let (width, height) = match image_convert_type {
     ImageConvertType::ColorfulHalfBlock => (frame.cols() / 2, frame.rows() / 2),
+    ImageConvertType::Braille | ImageConvertType::ColorfulBraille | ImageConvertType::Octant => {
+      (frame.cols() / 2, frame.rows() / 4)
+    }
+    ImageConvertType::Sextant => (frame.cols() / 2, frame.rows() / 3),
     _ => (frame.cols(), frame.rows()),
   };
 
@@ -208,7 +1065,7 @@ pub fn convert_frame_into_ascii(
           .min_by_key(|(i, j, _, _)| color_dist(&subpixels[*i], &subpixels[*j]))
           .unwrap();
 
-          let fg_color = color_average([subpixels[fg1], subpixels[fg2]]);
+          let fg_color = color_average(&[subpixels[fg1], subpixels[fg2]]);
 
           // Of the two remaining colors, is one of them closer to the
           // foreground color than they are to each other?
@@ -221,7 +1078,7 @@ pub fn convert_frame_into_ascii(
             // The two remaining colors are closer to each other
             // than they are to the foreground color.  They will
             // both share the same background color.
-            let bg_color = color_average([subpixels[other1], subpixels[other2]]);
+            let bg_color = color_average(&[subpixels[other1], subpixels[other2]]);
             let fg_char = match (fg1, fg2) {
               (0, 1) => '▀',
               (0, 2) => '▌',
@@ -236,7 +1093,7 @@ pub fn convert_frame_into_ascii(
             // The point at `other1` is close to the fg color.
             // Therefore, average those three into the foreground, and
             // use the background for the last color.
-            let fg_color = color_average([subpixels[fg1], subpixels[fg2], subpixels[other1]]);
+            let fg_color = color_average(&[subpixels[fg1], subpixels[fg2], subpixels[other1]]);
             let bg_color = subpixels[other2];
             let fg_char = match other2 {
               0 => '▟',
@@ -251,7 +1108,7 @@ pub fn convert_frame_into_ascii(
             // The point at `other2` is close to the fg color.
             // Therefore, average those three into the foreground, and
             // use the background for the last color.
-            let fg_color = color_average([subpixels[fg1], subpixels[fg2], subpixels[other2]]);
+            let fg_color = color_average(&[subpixels[fg1], subpixels[fg2], subpixels[other2]]);
             let bg_color = subpixels[other1];
             let fg_char = match other1 {
               0 => '▟',
@@ -270,11 +1127,11 @@ pub fn convert_frame_into_ascii(
           )
         }
         ImageConvertType::Colorful => {
-          let pixel = frame.at_2d::<opencv::core::Vec3b>(y, x).unwrap();
+          let pixel = frame.at_2d::<opencv::core::Vec3b>(y, x)?;
           ('█', Color::Rgb(pixel[2], pixel[1], pixel[0]), Color::Reset)
         }
         ImageConvertType::GrayScale => {
-          let intensity = frame.at_2d::<u8>(y, x).unwrap();
+          let intensity = frame.at_2d::<u8>(y, x)?;
 
           (
             '█',
@@ -283,7 +1140,7 @@ pub fn convert_frame_into_ascii(
           )
         }
         ImageConvertType::GrayScaleThreshold => {
-          let intensity = frame.at_2d::<u8>(y, x).unwrap();
+          let intensity = frame.at_2d::<u8>(y, x)?;
           let char_index =
             (*intensity as f32 * (ASCII_CHARS.len() - 1) as f32 / 255.0).round() as usize;
 
@@ -294,13 +1151,110 @@ pub fn convert_frame_into_ascii(
           )
         }
         ImageConvertType::Threshold => {
-          let intensity = frame.at_2d::<u8>(y, x).unwrap();
+          // The frame is binary already (0 or 255)
+          let intensity = frame.at_2d::<u8>(y, x)?;
           (
-            if *intensity > 150 { '█' } else { ' ' },
+            if *intensity > 0 { '█' } else { ' ' },
             Color::Rgb(255, 255, 255),
             Color::Reset,
           )
         }
+        // Plain glyphs only (no colors), so the output can be copied as text
+        ImageConvertType::Ascii => {
+          let intensity = frame.at_2d::<u8>(y, x)?;
+          (ascii_ramp.glyph(*intensity), Color::Reset, Color::Reset)
+        }
+        ImageConvertType::Braille => {
+          let bits = BRAILLE_DOTS
+            .iter()
+            .enumerate()
+            .filter(|(_, (dx, dy))| {
+              frame
+                .at_2d::<u8>(y * 4 + dy, x * 2 + dx)
+                .is_ok_and(|intensity| *intensity > 0)
+            })
+            .fold(0, |bits, (i, _)| bits | 1 << i);
+
+          (braille_char(bits), Color::Rgb(255, 255, 255), Color::Reset)
+        }
+        ImageConvertType::ColorfulBraille => {
+          let subpixels: [_; 8] = std::array::from_fn(|i| {
+            let (dx, dy) = BRAILLE_DOTS[i];
+
+            let pixel = frame
+              .at_2d::<opencv::core::Vec3b>(y * 4 + dy, x * 2 + dx)
+              .cloned()
+              .unwrap_or(opencv::core::Vec3b::from([255, 255, 255]));
+
+            [pixel[0], pixel[1], pixel[2]]
+          });
+
+          let luminances = subpixels.map(|subpixel| color_luminance(&subpixel));
+          let min_luminance = luminances.iter().min().copied().unwrap_or(0);
+          let max_luminance = luminances.iter().max().copied().unwrap_or(0);
+          let mean_luminance = luminances.iter().sum::<u32>() / 8;
+
+          // A flat cell is drawn with all dots, otherwise only the brighter dots are drawn
+          let bits = if max_luminance - min_luminance < BRAILLE_MIN_CONTRAST {
+            0xff
+          } else {
+            (0..8)
+              .filter(|i| luminances[*i] >= mean_luminance)
+              .fold(0, |bits, i| bits | 1 << i)
+          };
+
+          let lit_subpixels = (0..8)
+            .filter(|i| bits & (1 << i) != 0)
+            .map(|i| subpixels[i])
+            .collect::<Vec<_>>();
+
+          let fg_color = color_average(&lit_subpixels);
+
+          (
+            braille_char(bits),
+            Color::Rgb(fg_color[2], fg_color[1], fg_color[0]),
+            Color::Reset,
+          )
+        }
+        ImageConvertType::Sextant | ImageConvertType::Octant => {
+          // Subpixels are arranged row by row, left to right.
+          //
+          //  0 | 1
+          //  2 | 3
+          //  4 | 5
+          //  6 | 7 (octant only)
+          let rows = if image_convert_type == ImageConvertType::Sextant { 3 } else { 4 };
+
+          let mut subpixels = [[0; 3]; 8];
+          for (i, subpixel) in subpixels.iter_mut().take(rows as usize * 2).enumerate() {
+            let i = i as i32;
+
+            let pixel = frame
+              .at_2d::<opencv::core::Vec3b>(y * rows + i / 2, x * 2 + i % 2)
+              .cloned()
+              .unwrap_or(opencv::core::Vec3b::from([255, 255, 255]));
+
+            *subpixel = [pixel[0], pixel[1], pixel[2]];
+          }
+
+          let (bits, fg_color, bg_color) = cluster_subpixels(&subpixels[..rows as usize * 2]);
+
+          let glyph = if rows == 3 {
+            sextant_char(bits)
+          } else {
+            octant_char(bits)
+          };
+
+          (
+            glyph,
+            Color::Rgb(fg_color[2], fg_color[1], fg_color[0]),
+            Color::Rgb(bg_color[2], bg_color[1], bg_color[0]),
+          )
+        }
+        // Drawn by a graphics protocol
+        ImageConvertType::Kitty | ImageConvertType::Sixel | ImageConvertType::ITerm2 => {
+          (' ', Color::Reset, Color::Reset)
+        }
       };
 
       let style = Style::default().fg(fg_color).bg(bg_color);
@@ -310,76 +1264,250 @@ pub fn convert_frame_into_ascii(
     lines.push(Line::from(spans));
   }
 
-  Text::from(lines)
+  Ok(Text::from(lines))
 }
 
-pub struct FrameHandler {
+pub struct FrameHandler<S: FrameSource> {
   config: Arc<RwLock<FrameHandlerConfig>>,
   tx: tokio::sync::mpsc::UnboundedSender<AppEvent>,
+  source: S,
 }
 
-impl FrameHandler {
+impl<S: FrameSource + 'static> FrameHandler<S> {
   pub async fn try_new(
     config: Arc<RwLock<FrameHandlerConfig>>,
     tx: tokio::sync::mpsc::UnboundedSender<AppEvent>,
+    source: S,
   ) -> opencv::Result<Self> {
-    Ok(Self { config, tx })
-  }
-
-  pub fn get_cam(&self, cam_id: i32, cam: &mut Option<VideoCapture>) {
-    *cam = Some(VideoCapture::new(cam_id, videoio::CAP_ANY).unwrap());
+    Ok(Self { config, tx, source })
   }
 
   /// Spawns a new Tokio task.
   ///
-  /// This task opens a device camera, captures a frame, and resizes the image.
+  /// This task opens a frame source, captures a frame, and resizes the image.
   /// If frame is a GrayScale or Threshold converts into approriate format
-  pub async fn run(self) -> opencv::Result<()> {
-    let _handle = tokio::spawn(async move {
-      let (mut cam, mut active_cam_id) = (None, -1);
-
+  ///
+  /// Failures are sent as `AppEvent::Error`, the task then waits for a retry request.
+  /// The task ends on an exit request (returned handle).
+  pub async fn run(mut self) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
       let mut frame = opencv::core::Mat::default();
       let mut interval = tokio::time::interval(Duration::from_millis(50));
+      let mut kitty_encoder = KittyEncoder::new();
+      let mut video_recorder: Option<VideoRecorder> = None;
+
+      // The source is (re)opened on a retry request
+      let mut is_failed = false;
+
+      // A (re)opened source is converted even without a new frame
+      let mut is_redraw_needed = true;
+
+      // Mode, frame size and camera area of the last converted frame
+      let mut last_view = None;
+
+      self.source.needs_reopen(&*self.config.read().await);
+
+      let (source, open_result) = reopen(self.source).await;
+      self.source = source;
+
+      if let Err(err) = open_result {
+        is_failed = true;
+        let _ = self
+          .tx
+          .send(AppEvent::Error(TuicamError::Open(self.source.describe(), err)));
+      }
 
       loop {
+        interval.tick().await;
+
+        if self.config.read().await.is_exit_requested {
+          break;
+        }
+
+        if is_failed {
+          let mut config = self.config.write().await;
+
+          // A failed camera is released, so the camera watcher can probe it again
+          if !config.is_source_failed {
+            let _ = self.source.close();
+            config.is_source_failed = true;
+          }
+
+          if !std::mem::take(&mut config.is_retry_requested) {
+            continue;
+          }
+
+          config.is_source_failed = false;
+
+          // A switched input is taken over before the reopen
+          self.source.needs_reopen(&config);
+          drop(config);
+
+          let (source, open_result) = reopen(self.source).await;
+          self.source = source;
+
+          if let Err(err) = open_result {
+            let _ = self
+              .tx
+              .send(AppEvent::Error(TuicamError::Open(self.source.describe(), err)));
+            continue;
+          }
+
+          is_failed = false;
+          is_redraw_needed = true;
+        }
+
+        // A switched camera (or capture mode) is reopened outside of the config lock
+        if self.source.needs_reopen(&*self.config.read().await) {
+          let (source, open_result) = reopen(self.source).await;
+          self.source = source;
+
+          if let Err(err) = open_result {
+            is_failed = true;
+            let _ = self
+              .tx
+              .send(AppEvent::Error(TuicamError::Open(self.source.describe(), err)));
+            continue;
+          }
+
+          is_redraw_needed = true;
+        }
+
         let mut small_frame = opencv::core::Mat::default();
 
-        let current_cam_id = self
-          .config
-          .read()
-          .await
-          .camera
-          .get_cam_id()
-          .unwrap()
-          .clone();
+        let (snapshot, is_processed_recording, is_redraw_requested, view) = {
+          let mut config = self.config.write().await;
+
+          let sync_result = self.source.sync(&mut config);
+          config.source_description = self.source.describe();
+
+          if let Err(err) = sync_result {
+            is_failed = true;
+            let _ = self
+              .tx
+              .send(AppEvent::Error(TuicamError::Open(self.source.describe(), err)));
+            continue;
+          }
+
+          // A writer error ends the recording
+          if video_recorder.as_ref().is_some_and(|video_recorder| video_recorder.is_finished()) {
+            video_recorder = None;
+            config.is_recording = false;
+            config.recording_started_at = None;
+          }
+
+          match (config.is_recording, video_recorder.is_some()) {
+            (true, false) => {
+              let codec = config.record_options.codec;
+              let path = snapshot::timestamped_path(&config.snapshot_dir)
+                .with_extension(codec.extension());
+              let fps = 1.0 / self.source.frame_interval().as_secs_f64();
+
+              video_recorder = Some(VideoRecorder::start(path, codec, fps, self.tx.clone()));
+              config.recording_started_at = Some(Instant::now());
+            }
+            // Dropping the recorder finishes the file (waits for the writer)
+            (false, true) => {
+              if let Some(video_recorder) = video_recorder.take() {
+                tokio::task::spawn_blocking(move || drop(video_recorder));
+              }
+              config.recording_started_at = None;
+            }
+            _ => {}
+          }
+
+          let snapshot = std::mem::take(&mut config.is_snapshot_requested).then(|| {
+            let path = snapshot::timestamped_path(&config.snapshot_dir)
+              .with_extension(config.snapshot_format.extension());
+            (path, config.snapshot_format)
+          });
+
+          (
+            snapshot,
+            config.record_options.is_processed,
+            std::mem::take(&mut config.is_redraw_requested),
+            (config.image_convert_type.clone(), config.target_size(), config.cam_area),
+          )
+        };
+
+        // A paused video or a still image gives no new frame (the last one is kept)
+        let is_new_frame = match self.source.read(&mut frame) {
+          Ok(is_new_frame) => is_new_frame,
+          Err(err) => {
+            is_failed = true;
+            let _ = self
+              .tx
+              .send(AppEvent::Error(TuicamError::Read(self.source.describe(), err)));
+            continue;
+          }
+        };
 
-        if current_cam_id != active_cam_id {
-          self.get_cam(current_cam_id, &mut cam);
-          active_cam_id = current_cam_id;
+        // A disconnected camera gives empty frames
+        if frame.empty() {
+          is_failed = true;
+          let _ = self
+            .tx
+            .send(AppEvent::Error(TuicamError::NoFrame(self.source.describe())));
+          continue;
+        }
+
+        if let Some(video_recorder) = video_recorder.as_ref() {
+          if is_new_frame && !is_processed_recording {
+            video_recorder.push(&frame);
+          }
+        }
+
+        // Snapshots are taken from the raw frame (full resolution, no camera mode applied)
+        if let Some((path, snapshot_format)) = snapshot {
+          let raw_frame = frame.clone();
+          let tx = self.tx.clone();
+
+          tokio::task::spawn_blocking(move || {
+            let message = match snapshot::save(&raw_frame, &path, snapshot_format) {
+              Ok(()) => format!("Snapshot saved to {}", path.display()),
+              Err(err) => format!("Snapshot failed: {}", err.message),
+            };
+
+            let _ = tx.send(AppEvent::Toast(message));
+          });
         }
 
-        cam.as_mut().unwrap().read(&mut frame).unwrap();
+        if self.source.frame_interval() != interval.period() {
+          interval = tokio::time::interval(self.source.frame_interval());
+        }
+
+        // The same frame is converted again only if the view changed
+        let is_view_changed = last_view.as_ref() != Some(&view);
+
+        if !is_new_frame && !is_redraw_requested && !is_redraw_needed && !is_view_changed {
+          continue;
+        }
+
+        is_redraw_needed = false;
+        last_view = Some(view);
 
         let cam_size = {
           let config = self.config.read().await;
+          let cam_size = config.target_size();
 
-          let cam_size = opencv::core::Size {
-            width: (config.terminal_size.0 / config.cam_window_scale.clone() as u16) as i32,
-            height: (config.terminal_size.1 / config.cam_window_scale.clone() as u16) as i32,
-          };
-
+          // Never upscale, the terminal scales the image into the camera area
           match config.image_convert_type {
-            ImageConvertType::ColorfulHalfBlock => opencv::core::Size {
-              width: cam_size.width * 2,
-              height: cam_size.height * 2,
+            ImageConvertType::Kitty | ImageConvertType::ITerm2 => opencv::core::Size {
+              width: cam_size.width.min(frame.cols()),
+              height: cam_size.height.min(frame.rows()),
             },
             _ => cam_size,
           }
         };
 
-        // Some virtual cams crash on the resize call.
-        // If some error occurs just switch to an another cam.
-        if let Err(_) = opencv::imgproc::resize(
+        // The frame is converted once the camera area is known
+        if cam_size.width <= 0 || cam_size.height <= 0 {
+          is_redraw_needed = true;
+          continue;
+        }
+
+        // Some virtual cams crash on the resize call (the user can switch to an another cam)
+        if let Err(err) = opencv::imgproc::resize(
           &frame,
           &mut small_frame,
           cam_size,
@@ -387,51 +1515,97 @@ impl FrameHandler {
           0.0,
           opencv::imgproc::INTER_LINEAR,
         ) {
-          self.config.write().await.camera.switch();
+          is_failed = true;
+          let _ = self.tx.send(AppEvent::Error(TuicamError::Process(err)));
           continue;
         }
 
         let config = self.config.read().await;
-        let res_frame = match config.image_convert_type {
-          ImageConvertType::Colorful | ImageConvertType::ColorfulHalfBlock => small_frame.clone(),
-          ImageConvertType::GrayScale | ImageConvertType::GrayScaleThreshold => {
-            let mut gray_frame = opencv::core::Mat::default();
-            convert_into_grayscale(&small_frame, &mut gray_frame);
-            gray_frame
+
+        if config.is_mirrored {
+          let mut mirrored_frame = opencv::core::Mat::default();
+
+          if opencv::core::flip(&small_frame, &mut mirrored_frame, 1).is_ok() {
+            small_frame = mirrored_frame;
+          }
+        }
+
+        // Software brightness, contrast, gamma and saturation
+        let res_frame = config
+          .image_adjustments
+          .apply(&small_frame)
+          .and_then(|adjusted_frame| convert_frame(&adjusted_frame, &config));
+
+        let res_frame = match res_frame {
+          Ok(res_frame) => res_frame,
+          Err(err) => {
+            is_failed = true;
+            let _ = self.tx.send(AppEvent::Error(TuicamError::Process(err)));
+            continue;
+          }
+        };
+
+        if let Some(video_recorder) = video_recorder.as_ref() {
+          if is_new_frame && is_processed_recording {
+            video_recorder.push(&res_frame);
           }
-          ImageConvertType::Threshold => {
-            let mut gray_frame = opencv::core::Mat::default();
-            let mut binary_frame = opencv::core::Mat::default();
-
-            convert_into_grayscale(&small_frame, &mut gray_frame);
-
-            imgproc::threshold(
-              &gray_frame,
-              &mut binary_frame,
-              128.0,
-              255.0,
-              imgproc::THRESH_BINARY,
-            )
-            .unwrap();
-
-            binary_frame
+        }
+
+        let cam_area = config.cam_area;
+
+        let app_event = match config.image_convert_type.graphics_protocol() {
+          Some(GraphicsProtocol::Kitty) => kitty_encoder
+            .encode(&res_frame, cam_area.width, cam_area.height)
+            .map(|image| AppEvent::ImageFrame(GraphicsProtocol::Kitty, image))
+            .map_err(TuicamError::Encode),
+          Some(GraphicsProtocol::Sixel) => graphics::encode_sixel(&res_frame, config.dithering)
+            .map(|image| AppEvent::ImageFrame(GraphicsProtocol::Sixel, image))
+            .map_err(TuicamError::Encode),
+          Some(GraphicsProtocol::ITerm2) => {
+            graphics::encode_iterm2(&res_frame, cam_area.width, cam_area.height)
+              .map(|image| AppEvent::ImageFrame(GraphicsProtocol::ITerm2, image))
+              .map_err(TuicamError::Encode)
           }
+          None => convert_frame_into_ascii(
+            res_frame,
+            config.image_convert_type.clone(),
+            &config.ascii_ramp,
+          )
+          .map(AppEvent::AsciiFrame)
+          .map_err(TuicamError::Process),
         };
 
-        let ascii_frame = convert_frame_into_ascii(res_frame, config.image_convert_type.clone());
+        let app_event = app_event.unwrap_or_else(|err| {
+          is_failed = true;
+          AppEvent::Error(err)
+        });
 
-        if self.tx.send(AppEvent::AsciiFrame(ascii_frame)).is_err() {
+        if self.tx.send(app_event).is_err() {
           break;
         }
+      }
 
-        interval.tick().await;
+      // The file is finished before the task ends (the app waits for it on exit)
+      if let Some(video_recorder) = video_recorder.take() {
+        let _ = tokio::task::spawn_blocking(move || drop(video_recorder)).await;
       }
-    });
 
-    Ok(())
+      let _ = self.source.close();
+    })
   }
 }
 
+/// Closes and opens the source on a blocking thread, returns the source back
+async fn reopen<S: FrameSource + 'static>(mut source: S) -> (S, opencv::Result<()>) {
+  tokio::task::spawn_blocking(move || {
+    let _ = source.close();
+    let open_result = source.open();
+    (source, open_result)
+  })
+  .await
+  .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))
+}
+
 #[allow(unused)]
 pub struct EventHandler(pub tokio::task::JoinHandle<()>);
 
@@ -448,10 +1622,91 @@ impl EventHandler {
         let crossterm_event = reader.next().fuse().await;
 
         if let Some(Ok(event)) = crossterm_event {
-          match event {
-            Event::Key(key_code) => tx.send(AppEvent::Event(key_code)).unwrap(),
-            Event::Resize(width, height) => tx.send(AppEvent::TerminalResize((width, height))).unwrap(),
-            _ => {}
+          let send_result = match event {
+            Event::Key(key_code) => tx.send(AppEvent::Event(key_code)),
+            Event::Resize(width, height) => tx.send(AppEvent::TerminalResize((width, height))),
+            _ => Ok(()),
+          };
+
+          // The app is closed
+          if send_result.is_err() {
+            break;
+          }
+        }
+      }
+    });
+
+    Self(handle)
+  }
+}
+
+/// Camera hot-plug watcher
+pub struct CameraWatcher(pub tokio::task::JoinHandle<()>);
+
+impl CameraWatcher {
+  /// Spawns a new Tokio task.
+  ///
+  /// This task periodically rescans the device cameras and updates the switch cycle.
+  /// Connected and disconnected cameras are reported as a toast,
+  /// a stream of the active camera is reopened when the camera comes back.
+  pub fn new(
+    config: Arc<RwLock<FrameHandlerConfig>>,
+    tx: tokio::sync::mpsc::UnboundedSender<AppEvent>,
+  ) -> Self {
+    let handle = tokio::spawn(async move {
+      let mut interval = tokio::time::interval(CAMERA_RESCAN_INTERVAL);
+
+      loop {
+        interval.tick().await;
+
+        let (known_devices, busy_id) = {
+          let config = config.read().await;
+
+          // A file input never switches to a camera
+          if config.input.is_some() {
+            break;
+          }
+
+          // The streamed camera is held open by the frame handler
+          let busy_id = config
+            .camera
+            .get_cam_id()
+            .copied()
+            .filter(|_| config.pattern.is_none() && !config.is_source_failed);
+
+          (config.camera.devices.clone(), busy_id)
+        };
+
+        let Ok(devices) =
+          tokio::task::spawn_blocking(move || device::enumerate(&known_devices, busy_id)).await
+        else {
+          break;
+        };
+
+        let mut config = config.write().await;
+
+        let was_disconnected =
+          config.camera.get_cam_id().is_some() && !config.camera.is_connected();
+        let (connected, disconnected) = config.camera.update_devices(devices);
+
+        // The frame handler waits for a retry after the failure
+        if was_disconnected && config.camera.is_connected() {
+          config.is_retry_requested = true;
+        }
+
+        let messages = connected
+          .iter()
+          .map(|device| format!("{} connected", device.name))
+          .chain(
+            disconnected
+              .iter()
+              .map(|device| format!("{} disconnected", device.name)),
+          );
+
+        for message in messages {
+          // The app is closed
+          if tx.send(AppEvent::Toast(message)).is_err() {
+            return;
           }
         }
       }
@@ -460,3 +1715,147 @@ impl EventHandler {
     Self(handle)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::source::SyntheticSource;
+
+  /// Terminal size of the rendered frames (the camera window fills it)
+  const TERMINAL_SIZE: (u16, u16) = (32, 12);
+
+  /// Renders a tick of a test pattern the way the frame handler does
+  fn render(
+    image_convert_type: ImageConvertType,
+    pattern: TestPattern,
+    tick: u32,
+  ) -> Text<'static> {
+    let options = Options {
+      pattern: Some(pattern),
+      image_convert_type: image_convert_type.clone(),
+      cam_window_scale: CamWindowScale::Full,
+      graphics_protocols: Some(Vec::new()),
+      ..Options::default()
+    };
+
+    let config =
+      FrameHandlerConfig::try_new(Size::new(TERMINAL_SIZE.0, TERMINAL_SIZE.1), options).unwrap();
+
+    let mut source = SyntheticSource::new(pattern);
+    let mut frame = opencv::core::Mat::default();
+    let mut small_frame = opencv::core::Mat::default();
+
+    source.open().unwrap();
+
+    for _ in 0..=tick {
+      source.read(&mut frame).unwrap();
+    }
+
+    imgproc::resize(
+      &frame,
+      &mut small_frame,
+      config.target_size(),
+      0.0,
+      0.0,
+      imgproc::INTER_LINEAR,
+    )
+    .unwrap();
+
+    let res_frame = convert_frame(&small_frame, &config).unwrap();
+
+    convert_frame_into_ascii(res_frame, image_convert_type, &config.ascii_ramp).unwrap()
+  }
+
+  /// Returns the glyphs of a rendered line
+  fn glyphs(line: &Line) -> String {
+    line.spans.iter().map(|span| span.content.as_ref()).collect()
+  }
+
+  #[test]
+  fn render_text_modes() {
+    let text_modes = ImageConvertType::ALL
+      .into_iter()
+      .filter(|mode| mode.graphics_protocol().is_none());
+
+    for image_convert_type in text_modes {
+      let text = render(image_convert_type.clone(), TestPattern::BouncingBall, 10);
+
+      // One cell per glyph, the camera window is filled
+      assert_eq!(text.lines.len(), TERMINAL_SIZE.1 as usize, "{}", image_convert_type.name());
+
+      for line in text.lines.iter() {
+        assert_eq!(line.spans.len(), TERMINAL_SIZE.0 as usize, "{}", image_convert_type.name());
+      }
+
+      // The same tick always gives the same frame
+      assert_eq!(
+        text,
+        render(image_convert_type.clone(), TestPattern::BouncingBall, 10),
+        "{}",
+        image_convert_type.name()
+      );
+    }
+  }
+
+  #[test]
+  fn render_color_bars() {
+    let text = render(ImageConvertType::Colorful, TestPattern::ColorBars, 0);
+
+    let first_bar = &text.lines[0].spans[0];
+    let last_bar = &text.lines[0].spans[TERMINAL_SIZE.0 as usize - 1];
+
+    assert_eq!(first_bar.content, "█");
+    assert_eq!(first_bar.style.fg, Some(Color::Rgb(191, 191, 191)));
+    assert_eq!(last_bar.style.fg, Some(Color::Rgb(0, 0, 191)));
+  }
+
+  #[test]
+  fn render_checkerboard_threshold() {
+    let text = render(ImageConvertType::Threshold, TestPattern::Checkerboard, 0);
+
+    // A checker square is 2 cells wide and 1 cell high
+    for (y, line) in text.lines.iter().enumerate() {
+      let expected = (0..TERMINAL_SIZE.0 as usize)
+        .map(|x| if (x / 2 + y) % 2 == 0 { '█' } else { ' ' })
+        .collect::<String>();
+
+      assert_eq!(glyphs(line), expected);
+    }
+  }
+
+  #[test]
+  fn sextant_glyphs() {
+    assert_eq!(sextant_char(0), ' ');
+    assert_eq!(sextant_char(0b000001), '\u{1FB00}');
+    assert_eq!(sextant_char(0b010101), '▌');
+    assert_eq!(sextant_char(0b010110), '\u{1FB14}');
+    assert_eq!(sextant_char(0b101010), '▐');
+    assert_eq!(sextant_char(0b101011), '\u{1FB28}');
+    assert_eq!(sextant_char(0b111110), '\u{1FB3B}');
+    assert_eq!(sextant_char(0b111111), '█');
+  }
+
+  #[test]
+  fn sextant_glyphs_are_unique() {
+    let glyphs = (0..64).map(sextant_char).collect::<std::collections::HashSet<_>>();
+    assert_eq!(glyphs.len(), 64);
+  }
+
+  #[test]
+  fn octant_glyphs() {
+    assert_eq!(octant_char(0x00), ' ');
+    assert_eq!(octant_char(0x01), '\u{1CEA8}');
+    assert_eq!(octant_char(0x04), '\u{1CD00}');
+    assert_eq!(octant_char(0x06), '\u{1CD01}');
+    assert_eq!(octant_char(0x0F), '▀');
+    assert_eq!(octant_char(0xF0), '▄');
+    assert_eq!(octant_char(0xFE), '\u{1CDE5}');
+    assert_eq!(octant_char(0xFF), '█');
+  }
+
+  #[test]
+  fn octant_glyphs_are_unique() {
+    let glyphs = (0..=255).map(octant_char).collect::<std::collections::HashSet<_>>();
+    assert_eq!(glyphs.len(), 256);
+  }
+}
@@ -0,0 +1,77 @@
+use std::{io::Write, sync::Arc, time::Duration};
+
+use ratatui::layout::Size;
+use tokio::sync::RwLock;
+
+use crate::{
+  channel::{AppEvent, Channel},
+  cli::{HeadlessOptions, Options},
+  handler::{CamWindowScale, FrameHandler, FrameHandlerConfig},
+  source,
+};
+
+/// Maximal delay between two frames before a headless export gives up
+const HEADLESS_FRAME_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Renders a single frame without the TUI.
+///
+/// The frame is written into the output file or stdout (for scripting).
+pub async fn run(
+  mut options: Options,
+  headless_options: HeadlessOptions,
+) -> Result<(), Box<dyn std::error::Error>> {
+  let mut channel = Channel::new();
+
+  // The frame fills the whole headless size
+  options.cam_window_scale = CamWindowScale::Full;
+
+  let (cols, rows) = headless_options.size;
+  let config = Arc::new(RwLock::new(FrameHandlerConfig::try_new(
+    Size::new(cols, rows),
+    options,
+  )?));
+
+  let source = source::from_input(config.read().await.input.as_deref());
+
+  let frame_handler = FrameHandler::try_new(config.clone(), channel.get_tx(), source).await?;
+  let _frame_handler = frame_handler.run().await;
+
+  let mut skipped_frames = 0;
+
+  let text = loop {
+    match tokio::time::timeout(HEADLESS_FRAME_TIMEOUT, channel.next()).await {
+      Ok(Some(AppEvent::AsciiFrame(text))) => {
+        if skipped_frames >= headless_options.warmup {
+          break text;
+        }
+
+        skipped_frames += 1;
+      }
+      Ok(Some(AppEvent::Error(err))) => return Err(err.into()),
+      Ok(Some(_)) => {}
+      _ => {
+        let source_description = config.read().await.source_description.clone();
+        return Err(format!("No frame received from {}", source_description).into());
+      }
+    }
+  };
+
+  let content = headless_options.format.render(&text);
+
+  match headless_options.output {
+    Some(path) => {
+      if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir)?;
+      }
+
+      std::fs::write(path, content)?;
+    }
+    None => {
+      let mut stdout = std::io::stdout();
+      stdout.write_all(content.as_bytes())?;
+      stdout.flush()?;
+    }
+  }
+
+  Ok(())
+}
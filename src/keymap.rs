@@ -0,0 +1,701 @@
+use std::{
+  collections::BTreeMap,
+  time::{Duration, Instant},
+};
+
+use ratatui::crossterm::event::{KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use serde::{Deserialize, Serialize};
+
+/// Maximal delay between two keys of a key sequence
+const SEQUENCE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// User action triggered by a key binding
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum Action {
+  Exit,
+  SwitchMode,
+  SwitchCamera,
+  PickCamera,
+  SwitchCaptureMode,
+  ToggleControls,
+  ToggleAdjustments,
+  ToggleFullscreen,
+  Snapshot,
+  Export,
+  RecordVideo,
+  RecordCast,
+  RecordGif,
+  ToggleLock,
+  SwitchRamp,
+  InvertRamp,
+  SwitchThreshold,
+  LowerThreshold,
+  RaiseThreshold,
+  SwitchDithering,
+  TogglePause,
+  SeekBackward,
+  SeekForward,
+  SlowDown,
+  SpeedUp,
+  ToggleLoop,
+  Retry,
+  ResetSelected,
+  ResetAll,
+  SavePreset,
+}
+
+/// Help bar line of an action
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum ActionGroup {
+  General,
+  Ascii,
+  Threshold,
+  Dithering,
+  Playback,
+
+  // Shown in the error banner instead of the help bar
+  Error,
+
+  // Matched while a panel is open, shown in the panel hint
+  Panel,
+}
+
+impl Action {
+  /// All actions in the help bar order
+  pub const ALL: [Action; 30] = [
+    Action::Exit,
+    Action::SwitchMode,
+    Action::SwitchCamera,
+    Action::PickCamera,
+    Action::SwitchCaptureMode,
+    Action::ToggleControls,
+    Action::ToggleAdjustments,
+    Action::ToggleFullscreen,
+    Action::Snapshot,
+    Action::Export,
+    Action::RecordVideo,
+    Action::RecordCast,
+    Action::RecordGif,
+    Action::ToggleLock,
+    Action::SwitchRamp,
+    Action::InvertRamp,
+    Action::SwitchThreshold,
+    Action::LowerThreshold,
+    Action::RaiseThreshold,
+    Action::SwitchDithering,
+    Action::TogglePause,
+    Action::SeekBackward,
+    Action::SeekForward,
+    Action::SlowDown,
+    Action::SpeedUp,
+    Action::ToggleLoop,
+    Action::Retry,
+    Action::ResetSelected,
+    Action::ResetAll,
+    Action::SavePreset,
+  ];
+
+  /// Returns an action name (used by the config file)
+  pub fn name(&self) -> &'static str {
+    match self {
+      Action::Exit => "exit",
+      Action::SwitchMode => "switch-mode",
+      Action::SwitchCamera => "switch-camera",
+      Action::PickCamera => "pick-camera",
+      Action::SwitchCaptureMode => "switch-capture-mode",
+      Action::ToggleControls => "toggle-controls",
+      Action::ToggleAdjustments => "toggle-adjustments",
+      Action::ToggleFullscreen => "toggle-fullscreen",
+      Action::Snapshot => "snapshot",
+      Action::Export => "export",
+      Action::RecordVideo => "record-video",
+      Action::RecordCast => "record-cast",
+      Action::RecordGif => "record-gif",
+      Action::ToggleLock => "toggle-lock",
+      Action::SwitchRamp => "switch-ramp",
+      Action::InvertRamp => "invert-ramp",
+      Action::SwitchThreshold => "switch-threshold",
+      Action::LowerThreshold => "lower-threshold",
+      Action::RaiseThreshold => "raise-threshold",
+      Action::SwitchDithering => "switch-dithering",
+      Action::TogglePause => "toggle-pause",
+      Action::SeekBackward => "seek-backward",
+      Action::SeekForward => "seek-forward",
+      Action::SlowDown => "slow-down",
+      Action::SpeedUp => "speed-up",
+      Action::ToggleLoop => "toggle-loop",
+      Action::Retry => "retry",
+      Action::ResetSelected => "reset-selected",
+      Action::ResetAll => "reset-all",
+      Action::SavePreset => "save-preset",
+    }
+  }
+
+  /// Finds an action by the name
+  pub fn from_name(name: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|action| action.name() == name)
+  }
+
+  /// Returns a short description (shown in the help bar)
+  pub fn description(&self) -> &'static str {
+    match self {
+      Action::Exit => "exit",
+      Action::SwitchMode => "switch mode",
+      Action::SwitchCamera => "next camera",
+      Action::PickCamera => "pick camera",
+      Action::SwitchCaptureMode => "capture mode",
+      Action::ToggleControls => "controls",
+      Action::ToggleAdjustments => "adjust image",
+      Action::ToggleFullscreen => "toggle fullscreen",
+      Action::Snapshot => "snapshot",
+      Action::Export => "export",
+      Action::RecordVideo => "record video",
+      Action::RecordCast => "record cast",
+      Action::RecordGif => "record GIF",
+      Action::ToggleLock => "toggle lock",
+      Action::SwitchRamp => "switch ramp",
+      Action::InvertRamp => "invert ramp",
+      Action::SwitchThreshold => "threshold method",
+      Action::LowerThreshold => "lower",
+      Action::RaiseThreshold => "raise",
+      Action::SwitchDithering => "dithering",
+      Action::TogglePause => "play/pause",
+      Action::SeekBackward => "seek back",
+      Action::SeekForward => "seek forward",
+      Action::SlowDown => "slower",
+      Action::SpeedUp => "faster",
+      Action::ToggleLoop => "toggle loop",
+      Action::Retry => "retry",
+      Action::ResetSelected => "default",
+      Action::ResetAll => "reset all",
+      Action::SavePreset => "save",
+    }
+  }
+
+  /// Returns a help bar line of the action
+  pub fn group(&self) -> ActionGroup {
+    match self {
+      Action::SwitchRamp | Action::InvertRamp => ActionGroup::Ascii,
+      Action::SwitchThreshold | Action::LowerThreshold | Action::RaiseThreshold => {
+        ActionGroup::Threshold
+      }
+      Action::SwitchDithering => ActionGroup::Dithering,
+      Action::TogglePause
+      | Action::SeekBackward
+      | Action::SeekForward
+      | Action::SlowDown
+      | Action::SpeedUp
+      | Action::ToggleLoop => ActionGroup::Playback,
+      Action::Retry => ActionGroup::Error,
+      Action::ResetSelected | Action::ResetAll | Action::SavePreset => ActionGroup::Panel,
+      _ => ActionGroup::General,
+    }
+  }
+
+  /// Returns default key sequences of the action
+  fn default_bindings(&self) -> &'static [&'static str] {
+    match self {
+      Action::Exit => &["esc"],
+      Action::SwitchMode => &["m"],
+      Action::SwitchCamera => &["C"],
+      Action::PickCamera => &["c"],
+      Action::SwitchCaptureMode => &["R"],
+      Action::ToggleControls => &["o"],
+      Action::ToggleAdjustments => &["b"],
+      Action::ToggleFullscreen => &["f"],
+      Action::Snapshot => &["s"],
+      Action::Export => &["e"],
+      Action::RecordVideo => &["v"],
+      Action::RecordCast => &["a"],
+      Action::RecordGif => &["g"],
+      Action::ToggleLock => &["ctrl-space"],
+      Action::SwitchRamp => &["r"],
+      Action::InvertRamp => &["i"],
+      Action::SwitchThreshold => &["t"],
+      Action::LowerThreshold => &["["],
+      Action::RaiseThreshold => &["]"],
+      Action::SwitchDithering => &["d"],
+      Action::TogglePause => &["p"],
+      Action::SeekBackward => &["left"],
+      Action::SeekForward => &["right"],
+      Action::SlowDown => &["<"],
+      Action::SpeedUp => &[">"],
+      Action::ToggleLoop => &["l"],
+      Action::Retry => &["enter"],
+      Action::ResetSelected => &["d"],
+      Action::ResetAll => &["r"],
+      Action::SavePreset => &["s"],
+    }
+  }
+}
+
+/// Key sequences of an action in the config file (a single one or a list)
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeyBindings {
+  One(String),
+  Many(Vec<String>),
+}
+
+impl KeyBindings {
+  fn sequences(&self) -> Vec<&str> {
+    match self {
+      KeyBindings::One(sequence) => vec![sequence.as_str()],
+      KeyBindings::Many(sequences) => sequences.iter().map(|sequence| sequence.as_str()).collect(),
+    }
+  }
+}
+
+/// Key with modifiers
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub struct Key {
+  code: KeyCode,
+  modifiers: KeyModifiers,
+}
+
+impl Key {
+  /// Creates a key of the key event.
+  ///
+  /// Shift is a part of the typed character, so it's ignored for characters.
+  fn from_event(key_event: &KeyEvent) -> Self {
+    let mut modifiers =
+      key_event.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+
+    if matches!(key_event.code, KeyCode::Char(_)) {
+      modifiers.remove(KeyModifiers::SHIFT);
+    }
+
+    Self {
+      code: key_event.code,
+      modifiers,
+    }
+  }
+
+  /// Parses a key such as `m`, `ctrl-space`, `alt-left` or `shift-tab`
+  fn parse(key: &str) -> Result<Self, String> {
+    let mut modifiers = KeyModifiers::NONE;
+    let mut name = key;
+
+    // A trailing `-` is the minus key itself
+    while let Some((modifier, rest)) = name.split_once('-').filter(|(_, rest)| !rest.is_empty()) {
+      modifiers |= match modifier.to_lowercase().as_str() {
+        "ctrl" => KeyModifiers::CONTROL,
+        "alt" => KeyModifiers::ALT,
+        "shift" => KeyModifiers::SHIFT,
+        _ => return Err(format!("unknown modifier {} in key {}", modifier, key)),
+      };
+
+      name = rest;
+    }
+
+    let code = match name.to_lowercase().as_str() {
+      "esc" => KeyCode::Esc,
+      "enter" => KeyCode::Enter,
+      "space" => KeyCode::Char(' '),
+      "tab" => KeyCode::Tab,
+      "backspace" => KeyCode::Backspace,
+      "delete" => KeyCode::Delete,
+      "insert" => KeyCode::Insert,
+      "home" => KeyCode::Home,
+      "end" => KeyCode::End,
+      "pageup" => KeyCode::PageUp,
+      "pagedown" => KeyCode::PageDown,
+      "left" => KeyCode::Left,
+      "right" => KeyCode::Right,
+      "up" => KeyCode::Up,
+      "down" => KeyCode::Down,
+      lower_name => {
+        let mut chars = name.chars();
+
+        match (chars.next(), chars.next()) {
+          (Some(c), None) => KeyCode::Char(c),
+          _ => match lower_name
+            .strip_prefix('f')
+            .and_then(|n| n.parse::<u8>().ok())
+          {
+            Some(n @ 1..=12) => KeyCode::F(n),
+            _ => return Err(format!("unknown key {}", key)),
+          },
+        }
+      }
+    };
+
+    // Shifted characters are matched by the character
+    if let KeyCode::Char(c) = code {
+      if modifiers.contains(KeyModifiers::SHIFT) {
+        modifiers.remove(KeyModifiers::SHIFT);
+        return Ok(Self {
+          code: KeyCode::Char(c.to_ascii_uppercase()),
+          modifiers,
+        });
+      }
+    }
+
+    Ok(Self { code, modifiers })
+  }
+
+  /// Returns a key label (shown in the help bar)
+  fn label(&self) -> String {
+    let mut label = String::new();
+
+    if self.modifiers.contains(KeyModifiers::CONTROL) {
+      label += "ctrl-";
+    }
+
+    if self.modifiers.contains(KeyModifiers::ALT) {
+      label += "alt-";
+    }
+
+    if self.modifiers.contains(KeyModifiers::SHIFT) {
+      label += "shift-";
+    }
+
+    label += &match self.code {
+      KeyCode::Esc => String::from("ESC"),
+      KeyCode::Enter => String::from("enter"),
+      KeyCode::Char(' ') => String::from("<space>"),
+      KeyCode::Char(c) => c.to_string(),
+      KeyCode::Tab => String::from("tab"),
+      KeyCode::Backspace => String::from("backspace"),
+      KeyCode::Delete => String::from("delete"),
+      KeyCode::Insert => String::from("insert"),
+      KeyCode::Home => String::from("home"),
+      KeyCode::End => String::from("end"),
+      KeyCode::PageUp => String::from("pageup"),
+      KeyCode::PageDown => String::from("pagedown"),
+      KeyCode::Left => String::from("←"),
+      KeyCode::Right => String::from("→"),
+      KeyCode::Up => String::from("↑"),
+      KeyCode::Down => String::from("↓"),
+      KeyCode::F(n) => format!("f{}", n),
+      _ => String::from("?"),
+    };
+
+    label
+  }
+}
+
+/// Parses a key sequence of space separated keys (`g g`, `ctrl-x s`)
+fn parse_sequence(sequence: &str) -> Result<Vec<Key>, String> {
+  let keys = sequence
+    .split_whitespace()
+    .map(Key::parse)
+    .collect::<Result<Vec<_>, _>>()?;
+
+  if keys.is_empty() {
+    return Err(String::from("empty key sequence"));
+  }
+
+  Ok(keys)
+}
+
+/// Returns true if the action is matched in a panel only
+fn is_panel_action(action: Action) -> bool {
+  action.group() == ActionGroup::Panel
+}
+
+/// Key sequences bound to the actions.
+///
+/// Keys of a sequence are buffered until they match a binding (or can't match any).
+pub struct Keymap {
+  bindings: Vec<(Vec<Key>, Action)>,
+  pending: Vec<Key>,
+  pending_at: Instant,
+}
+
+impl Default for Keymap {
+  fn default() -> Self {
+    Self::new(&BTreeMap::new()).expect("default key bindings are valid")
+  }
+}
+
+impl Keymap {
+  /// Creates a keymap of the default bindings overridden by the config file bindings.
+  ///
+  /// Fails on unknown actions, invalid keys and ambiguous sequences.
+  pub fn new(overrides: &BTreeMap<String, KeyBindings>) -> Result<Self, String> {
+    for name in overrides.keys() {
+      if Action::from_name(name).is_none() {
+        return Err(format!("unknown action {} in keys", name));
+      }
+    }
+
+    let mut bindings = Vec::new();
+
+    for action in Action::ALL {
+      let sequences = match overrides.get(action.name()) {
+        Some(key_bindings) => key_bindings.sequences(),
+        None => action.default_bindings().to_vec(),
+      };
+
+      for sequence in sequences {
+        let keys =
+          parse_sequence(sequence).map_err(|err| format!("{} of {}", err, action.name()))?;
+        bindings.push((keys, action));
+      }
+    }
+
+    // An exact match is taken immediately, so a longer sequence would be unreachable
+    for (i, (keys, action)) in bindings.iter().enumerate() {
+      for (other_keys, other_action) in bindings.iter().skip(i + 1) {
+        let len = keys.len().min(other_keys.len());
+
+        if keys[..len] != other_keys[..len] {
+          continue;
+        }
+
+        // Panel actions shadow the same or a longer sequence of the other actions while
+        // a panel is open, a shorter one of the other actions would take the panel keys
+        let is_conflict = match (is_panel_action(*action), is_panel_action(*other_action)) {
+          (true, false) => keys.len() > other_keys.len(),
+          (false, true) => other_keys.len() > keys.len(),
+          _ => true,
+        };
+
+        if is_conflict {
+          return Err(format!(
+            "key sequences of {} and {} conflict",
+            action.name(),
+            other_action.name()
+          ));
+        }
+      }
+    }
+
+    Ok(Self {
+      bindings,
+      pending: Vec::new(),
+      pending_at: Instant::now(),
+    })
+  }
+
+  /// Returns an action of the pressed key (None while a sequence is incomplete)
+  pub fn handle(&mut self, key_event: &KeyEvent) -> Option<Action> {
+    self.handle_scoped(key_event, false)
+  }
+
+  /// Returns an action of the key pressed in a panel.
+  ///
+  /// Panel actions take precedence over the other actions bound to the same keys.
+  pub fn handle_panel(&mut self, key_event: &KeyEvent) -> Option<Action> {
+    self.handle_scoped(key_event, true)
+  }
+
+  fn handle_scoped(&mut self, key_event: &KeyEvent, is_panel: bool) -> Option<Action> {
+    if key_event.kind != KeyEventKind::Press {
+      return None;
+    }
+
+    if self.pending_at.elapsed() > SEQUENCE_TIMEOUT {
+      self.pending.clear();
+    }
+
+    let key = Key::from_event(key_event);
+
+    self.pending.push(key);
+    self.pending_at = Instant::now();
+
+    if let Some(action) = self.take_match(is_panel) {
+      return action;
+    }
+
+    // A broken sequence is dropped, the key may start a new one
+    if self.pending.is_empty() {
+      self.pending.push(key);
+      return self.take_match(is_panel).flatten();
+    }
+
+    None
+  }
+
+  /// Finds a binding of the pending keys.
+  ///
+  /// Returns None if nothing matches, the pending keys are kept for a prefix.
+  fn take_match(&mut self, is_panel: bool) -> Option<Option<Action>> {
+    let bindings = self
+      .bindings
+      .iter()
+      .filter(|(_, action)| is_panel || !is_panel_action(*action));
+
+    let matched = bindings
+      .clone()
+      .filter(|(keys, _)| *keys == self.pending)
+      .map(|(_, action)| *action)
+      .min_by_key(|action| !is_panel_action(*action));
+
+    if let Some(action) = matched {
+      self.pending.clear();
+      return Some(Some(action));
+    }
+
+    let is_prefix = bindings
+      .into_iter()
+      .any(|(keys, _)| keys.starts_with(&self.pending));
+
+    if is_prefix {
+      return Some(None);
+    }
+
+    let is_single_key = self.pending.len() == 1;
+    self.pending.clear();
+
+    // Don't retry a single unbound key
+    if is_single_key {
+      return Some(None);
+    }
+
+    None
+  }
+
+  /// Returns a label of the first key sequence of the action (None if unbound)
+  pub fn label(&self, action: Action) -> Option<String> {
+    let (keys, _) = self.bindings.iter().find(|(_, bound)| *bound == action)?;
+
+    Some(
+      keys
+        .iter()
+        .map(|key| key.label())
+        .collect::<Vec<_>>()
+        .join(" "),
+    )
+  }
+
+  /// Returns help bar entries (key label and description) of the action group.
+  ///
+  /// Only the first binding of an action is shown, unbound actions are skipped.
+  pub fn help(&self, group: ActionGroup) -> Vec<(String, &'static str)> {
+    Action::ALL
+      .iter()
+      .filter(|action| action.group() == group)
+      .filter_map(|action| Some((self.label(*action)?, action.description())))
+      .collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn key(code: KeyCode, modifiers: KeyModifiers) -> Key {
+    Key { code, modifiers }
+  }
+
+  fn overrides(bindings: &[(&str, &str)]) -> BTreeMap<String, KeyBindings> {
+    bindings
+      .iter()
+      .map(|(name, sequence)| (name.to_string(), KeyBindings::One(sequence.to_string())))
+      .collect()
+  }
+
+  #[test]
+  fn parse_sequence_of_keys() {
+    assert_eq!(
+      parse_sequence("ctrl-x s"),
+      Ok(vec![
+        key(KeyCode::Char('x'), KeyModifiers::CONTROL),
+        key(KeyCode::Char('s'), KeyModifiers::NONE),
+      ])
+    );
+    assert_eq!(
+      parse_sequence("alt-left f5"),
+      Ok(vec![
+        key(KeyCode::Left, KeyModifiers::ALT),
+        key(KeyCode::F(5), KeyModifiers::NONE),
+      ])
+    );
+    assert_eq!(
+      parse_sequence("shift-tab space"),
+      Ok(vec![
+        key(KeyCode::Tab, KeyModifiers::SHIFT),
+        key(KeyCode::Char(' '), KeyModifiers::NONE),
+      ])
+    );
+  }
+
+  #[test]
+  fn parse_sequence_of_special_characters() {
+    // Shifted characters are matched by the character
+    assert_eq!(
+      parse_sequence("shift-a"),
+      Ok(vec![key(KeyCode::Char('A'), KeyModifiers::NONE)])
+    );
+    // A trailing `-` is the minus key itself
+    assert_eq!(
+      parse_sequence("ctrl--"),
+      Ok(vec![key(KeyCode::Char('-'), KeyModifiers::CONTROL)])
+    );
+    assert_eq!(
+      parse_sequence("-"),
+      Ok(vec![key(KeyCode::Char('-'), KeyModifiers::NONE)])
+    );
+  }
+
+  #[test]
+  fn parse_invalid_sequence() {
+    assert_eq!(parse_sequence("  "), Err(String::from("empty key sequence")));
+    assert_eq!(parse_sequence("f13"), Err(String::from("unknown key f13")));
+    assert_eq!(parse_sequence("gg"), Err(String::from("unknown key gg")));
+    assert_eq!(
+      parse_sequence("meta-x"),
+      Err(String::from("unknown modifier meta in key meta-x"))
+    );
+  }
+
+  #[test]
+  fn default_keymap_is_valid() {
+    assert!(Keymap::new(&BTreeMap::new()).is_ok());
+  }
+
+  #[test]
+  fn reject_unknown_action() {
+    assert_eq!(
+      Keymap::new(&overrides(&[("fly", "z")])).err(),
+      Some(String::from("unknown action fly in keys"))
+    );
+  }
+
+  #[test]
+  fn reject_same_sequence() {
+    assert_eq!(
+      Keymap::new(&overrides(&[("snapshot", "z"), ("export", "z")])).err(),
+      Some(String::from("key sequences of snapshot and export conflict"))
+    );
+  }
+
+  #[test]
+  fn reject_prefix_sequence() {
+    assert_eq!(
+      Keymap::new(&overrides(&[("snapshot", "z"), ("export", "z x")])).err(),
+      Some(String::from("key sequences of snapshot and export conflict"))
+    );
+  }
+
+  #[test]
+  fn allow_panel_sequence_shadowing_global_one() {
+    // The panel keys are taken by the panel actions while a panel is open
+    assert!(Keymap::new(&overrides(&[("snapshot", "z"), ("save-preset", "z")])).is_ok());
+    assert!(Keymap::new(&overrides(&[("snapshot", "z x"), ("save-preset", "z")])).is_ok());
+  }
+
+  #[test]
+  fn reject_global_sequence_prefixing_panel_one() {
+    // The global key would be matched before the rest of the panel sequence
+    assert_eq!(
+      Keymap::new(&overrides(&[("snapshot", "z"), ("save-preset", "z x")])).err(),
+      Some(String::from("key sequences of snapshot and save-preset conflict"))
+    );
+  }
+
+  #[test]
+  fn handle_key_sequence() {
+    let mut keymap = Keymap::new(&overrides(&[("snapshot", "z x")])).unwrap();
+
+    let press = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+
+    assert_eq!(keymap.handle(&press('z')), None);
+    assert_eq!(keymap.handle(&press('x')), Some(Action::Snapshot));
+    // A broken sequence is dropped, the key starts a new one
+    assert_eq!(keymap.handle(&press('z')), None);
+    assert_eq!(keymap.handle(&press('m')), Some(Action::SwitchMode));
+  }
+}
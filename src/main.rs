@@ -1,16 +1,126 @@
+mod adjust;
+mod animation;
 mod app;
+mod cast;
 mod channel;
+mod cli;
+mod config;
+mod controls;
+mod device;
+mod dither;
+mod error;
+mod export;
+mod font;
+mod graphics;
 mod handler;
+mod headless;
+mod keymap;
+mod panel;
+mod pattern;
+mod picker;
+mod ramp;
+mod recorder;
+mod snapshot;
+mod source;
+mod threshold;
+
+use std::process::ExitCode;
 
 use app::App;
+use cli::{Command, Options};
+use config::ConfigFile;
+use handler::Camera;
+
+/// Prints available device cameras with their capabilities
+fn list_cameras() {
+  let camera = Camera::default();
+
+  if camera.devices.is_empty() {
+    println!("No camera found");
+    return;
+  }
+
+  for device in camera.devices {
+    println!("{}: {}", device.id, device.name);
+
+    if let Some(path) = device.path.as_ref() {
+      println!("  path: {}", path.display());
+    }
+
+    if let Some(stable_path) = device.stable_path.as_ref() {
+      println!("  stable path: {}", stable_path.display());
+    }
+
+    if let Some(bus_info) = device.bus_info.as_ref() {
+      println!("  bus: {}", bus_info);
+    }
+
+    for format in device.formats {
+      println!("  {} ({})", format.fourcc, format.description);
+
+      for resolution in format.resolutions {
+        println!("    {}", resolution);
+      }
+    }
+  }
+}
 
 #[tokio::main(flavor = "multi_thread")]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
+  // Printed with Display, config and option errors are plain messages
+  match run().await {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(err) => {
+      eprintln!("Error: {}", err);
+      ExitCode::FAILURE
+    }
+  }
+}
+
+/// Runs a command given on the command line
+async fn run() -> Result<(), Box<dyn std::error::Error>> {
+  let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+  // Commands without the config options work with a broken config file as well
+  match Command::parse(args.clone(), Options::default())? {
+    Command::ListCameras => {
+      opencv::core::set_log_level(opencv::core::LogLevel::LOG_LEVEL_SILENT)?;
+      list_cameras();
+      return Ok(());
+    }
+    Command::Help => {
+      cli::print_help();
+      return Ok(());
+    }
+    Command::Version => {
+      cli::print_version();
+      return Ok(());
+    }
+    Command::Run(_) | Command::Headless(..) => {}
+  }
+
+  let config_file = ConfigFile::load(cli::config_path(&args))?;
+
+  let options = match Command::parse(args, config_file.options()?)? {
+    Command::Run(options) => options,
+    Command::Headless(options, headless_options) => {
+      opencv::core::set_log_level(opencv::core::LogLevel::LOG_LEVEL_SILENT)?;
+      return headless::run(options, headless_options).await;
+    }
+    // Handled before the config file is loaded
+    Command::ListCameras | Command::Help | Command::Version => return Ok(()),
+  };
+
   let mut terminal = ratatui::init();
 
   opencv::core::set_log_level(opencv::core::LogLevel::LOG_LEVEL_SILENT)?;
 
-  let app_result = App::try_new(&mut terminal).await?.run().await;
+  let app = App::try_new(&mut terminal, options, config_file).await;
+
+  let app_result = match app {
+    Ok(mut app) => app.run().await,
+    Err(err) => Err(err),
+  };
 
   ratatui::restore();
 
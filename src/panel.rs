@@ -0,0 +1,271 @@
+use ratatui::{
+  style::{Color, Style, Stylize},
+  text::{Line, Span, Text},
+};
+
+use crate::{
+  adjust::Adjustment,
+  controls::{CameraControl, ControlRange},
+  handler::FrameHandlerConfig,
+};
+
+/// Slider width in cells
+const SLIDER_WIDTH: usize = 16;
+
+/// Number of coarse steps of a slider (a fine step is the control step)
+const SLIDER_STEPS: f64 = 32.0;
+
+/// Width of the control labels in cells
+const LABEL_WIDTH: usize = 18;
+
+/// Camera controls panel.
+///
+/// Sliders of the controls supported by the active camera, a preset is saved
+/// into the config file.
+pub struct ControlsPanel {
+  selected: CameraControl,
+}
+
+impl ControlsPanel {
+  pub fn new(config: &FrameHandlerConfig) -> Self {
+    Self {
+      selected: controls(config)
+        .first()
+        .copied()
+        .unwrap_or(CameraControl::Brightness),
+    }
+  }
+
+  /// Selects a previous (`-1`) or a next (`1`) control (wraps around)
+  pub fn select(&mut self, config: &FrameHandlerConfig, offset: isize) {
+    let controls = controls(config);
+
+    if controls.is_empty() {
+      return;
+    }
+
+    let index = controls
+      .iter()
+      .position(|control| *control == self.selected)
+      .unwrap_or(0) as isize;
+    let len = controls.len() as isize;
+
+    self.selected = controls[(index + offset).rem_euclid(len) as usize];
+  }
+
+  /// Moves a slider of the selected control (a switch is toggled).
+  ///
+  /// A coarse step is a part of the range, a fine step is the control step.
+  /// The automatic mode locking the value is switched off.
+  pub fn adjust(&self, config: &mut FrameHandlerConfig, direction: f64, is_fine: bool) {
+    let control = self.selected;
+
+    let Some(value) = config.camera_controls.value(control) else {
+      return;
+    };
+
+    if let Some((off_value, on_value)) = control.switch_values() {
+      let value = if value == off_value {
+        on_value
+      } else {
+        off_value
+      };
+      config.camera_controls.set(control, value);
+      return;
+    }
+
+    let range = control_range(config, control);
+
+    let step = match range {
+      Some(range) if !is_fine => {
+        ((range.max - range.min) / SLIDER_STEPS / range.step)
+          .ceil()
+          .max(1.0)
+          * range.step
+      }
+      Some(range) => range.step,
+      None => 1.0,
+    };
+
+    let mut value = value + step * direction;
+
+    if let Some(range) = range {
+      value = value.clamp(range.min, range.max);
+    }
+
+    if config.camera_controls.is_auto(control) {
+      if let Some(auto_control) = control.auto_control() {
+        let (off_value, _) = auto_control.switch_values().unwrap_or_default();
+        config.camera_controls.set(auto_control, off_value);
+      }
+    }
+
+    config.camera_controls.set(control, value);
+  }
+
+  /// Resets the selected control to the camera default
+  pub fn reset(&self, config: &mut FrameHandlerConfig) {
+    if let Some(range) = control_range(config, self.selected) {
+      config.camera_controls.set(self.selected, range.default);
+    }
+  }
+
+  /// Returns panel lines (a slider per control) with the camera name
+  pub fn text(&self, config: &FrameHandlerConfig, color: Color) -> (Text<'static>, String) {
+    let name = config
+      .camera
+      .active_device()
+      .map(|device| device.name.clone())
+      .unwrap_or_default();
+
+    let controls = controls(config);
+
+    if controls.is_empty() {
+      return (Text::from("No supported controls"), name);
+    }
+
+    let lines = controls
+      .into_iter()
+      .map(|control| {
+        let value = config.camera_controls.value(control).unwrap_or_default();
+        let is_auto = config.camera_controls.is_auto(control);
+
+        let slider = match (control.switch_values(), control_range(config, control)) {
+          (Some((off_value, _)), _) => {
+            let state = if value == off_value { "off" } else { "on" };
+            format!("{:<width$}", state, width = SLIDER_WIDTH)
+          }
+          (None, Some(range)) => slider(range.position(value)),
+          (None, None) => " ".repeat(SLIDER_WIDTH),
+        };
+
+        let value = if is_auto {
+          String::from("auto")
+        } else {
+          format_value(value)
+        };
+
+        slider_line(
+          control.label(),
+          slider,
+          value,
+          is_auto,
+          control == self.selected,
+          color,
+        )
+      })
+      .collect::<Vec<_>>();
+
+    (Text::from(lines), name)
+  }
+}
+
+/// Image adjustments panel (sliders of the software adjustments)
+pub struct AdjustmentsPanel {
+  selected: Adjustment,
+}
+
+impl AdjustmentsPanel {
+  pub fn new() -> Self {
+    Self {
+      selected: Adjustment::Brightness,
+    }
+  }
+
+  /// Selects a previous (`-1`) or a next (`1`) adjustment (wraps around)
+  pub fn select(&mut self, offset: isize) {
+    let index = Adjustment::ALL
+      .iter()
+      .position(|adjustment| *adjustment == self.selected)
+      .unwrap_or(0) as isize;
+    let len = Adjustment::ALL.len() as isize;
+
+    self.selected = Adjustment::ALL[(index + offset).rem_euclid(len) as usize];
+  }
+
+  /// Moves a slider of the selected adjustment by a step
+  pub fn adjust(&self, config: &mut FrameHandlerConfig, direction: f64) {
+    config.image_adjustments.step(self.selected, direction);
+  }
+
+  /// Resets the selected adjustment
+  pub fn reset(&self, config: &mut FrameHandlerConfig) {
+    let default = self.selected.range().default;
+    config.image_adjustments.set(self.selected, default);
+  }
+
+  /// Returns panel lines (a slider per adjustment)
+  pub fn text(&self, config: &FrameHandlerConfig, color: Color) -> Text<'static> {
+    let lines = Adjustment::ALL
+      .into_iter()
+      .map(|adjustment| {
+        let value = config.image_adjustments.value(adjustment);
+
+        slider_line(
+          adjustment.label(),
+          slider(adjustment.range().position(value)),
+          format_value(value),
+          false,
+          adjustment == self.selected,
+          color,
+        )
+      })
+      .collect::<Vec<_>>();
+
+    Text::from(lines)
+  }
+}
+
+/// Returns a panel line of a slider
+fn slider_line(
+  label: &str,
+  slider: String,
+  value: String,
+  is_dim: bool,
+  is_selected: bool,
+  color: Color,
+) -> Line<'static> {
+  let mut line = Line::from(vec![
+    Span::from(format!("{:<width$}", label, width = LABEL_WIDTH)),
+    Span::from(slider),
+    Span::from(format!(" {:>6}", value)),
+  ]);
+
+  if is_dim {
+    line = line.dim();
+  }
+
+  if is_selected {
+    line = line.style(Style::default().fg(color).reversed());
+  }
+
+  line
+}
+
+/// Returns a slider of the position in the range (0.0 - 1.0)
+fn slider(position: f64) -> String {
+  let filled = (position * SLIDER_WIDTH as f64).round() as usize;
+  "█".repeat(filled) + &"░".repeat(SLIDER_WIDTH - filled)
+}
+
+/// Returns a value rounded to two decimals
+fn format_value(value: f64) -> String {
+  format!("{}", (value * 100.0).round() / 100.0)
+}
+
+/// Returns the controls supported by the active camera (the ones with a value)
+fn controls(config: &FrameHandlerConfig) -> Vec<CameraControl> {
+  CameraControl::ALL
+    .into_iter()
+    .filter(|control| config.camera_controls.value(*control).is_some())
+    .collect()
+}
+
+/// Returns a value range of the control (if the camera reports it)
+fn control_range(config: &FrameHandlerConfig, control: CameraControl) -> Option<ControlRange> {
+  config
+    .camera
+    .active_device()
+    .and_then(|device| device.controls.get(&control))
+    .copied()
+}
@@ -0,0 +1,234 @@
+use opencv::{
+  core::{Mat, Point, Scalar, CV_8UC3},
+  imgproc,
+  prelude::*,
+};
+
+/// Test pattern frame size (width, height)
+pub const PATTERN_FRAME_SIZE: (i32, i32) = (640, 480);
+
+/// Duration of one generated frame in milliseconds (used for the timestamp)
+const PATTERN_TICK_MS: u32 = 50;
+
+/// Checkerboard square size in pixels
+const CHECKER_SIZE: i32 = 40;
+
+/// Bouncing ball radius in pixels
+const BALL_RADIUS: i32 = 36;
+
+/// SMPTE color bars (top row, 75% intensity) in RGB
+const SMPTE_BARS: [[u8; 3]; 7] = [
+  [191, 191, 191],
+  [191, 191, 0],
+  [0, 191, 191],
+  [0, 191, 0],
+  [191, 0, 191],
+  [191, 0, 0],
+  [0, 0, 191],
+];
+
+/// SMPTE reverse blue bars (middle row) in RGB
+const SMPTE_REVERSE_BARS: [[u8; 3]; 7] = [
+  [0, 0, 191],
+  [19, 19, 19],
+  [191, 0, 191],
+  [19, 19, 19],
+  [0, 191, 191],
+  [19, 19, 19],
+  [191, 191, 191],
+];
+
+/// Built-in synthetic test pattern
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum TestPattern {
+  ColorBars,
+  Gradient,
+  Checkerboard,
+  Noise,
+  BouncingBall,
+}
+
+impl TestPattern {
+  /// All test patterns in the switch order
+  pub const ALL: [TestPattern; 5] = [
+    TestPattern::ColorBars,
+    TestPattern::Gradient,
+    TestPattern::Checkerboard,
+    TestPattern::Noise,
+    TestPattern::BouncingBall,
+  ];
+
+  /// Returns a pattern name (used by the CLI)
+  pub fn name(&self) -> &'static str {
+    match self {
+      TestPattern::ColorBars => "bars",
+      TestPattern::Gradient => "gradient",
+      TestPattern::Checkerboard => "checkerboard",
+      TestPattern::Noise => "noise",
+      TestPattern::BouncingBall => "ball",
+    }
+  }
+
+  /// Finds a pattern by the name
+  pub fn from_name(name: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|pattern| pattern.name() == name)
+  }
+
+  /// Returns a next pattern or None after the last one
+  pub fn next(&self) -> Option<Self> {
+    let index = Self::ALL.iter().position(|pattern| pattern == self)?;
+    Self::ALL.get(index + 1).copied()
+  }
+
+  /// Generates a BGR frame of the pattern.
+  ///
+  /// The output depends on the `tick` only, so the same tick always gives the same frame.
+  pub fn generate(&self, tick: u32, frame: &mut Mat) -> opencv::Result<()> {
+    let (width, height) = PATTERN_FRAME_SIZE;
+
+    *frame = Mat::new_rows_cols_with_default(height, width, CV_8UC3, Scalar::all(0.0))?;
+
+    let data = frame.data_bytes_mut()?;
+
+    match self {
+      TestPattern::ColorBars => fill(data, width, height, color_bars),
+      TestPattern::Gradient => fill(data, width, height, gradient),
+      TestPattern::Checkerboard => {
+        let shift = (tick * 2) as i32;
+        fill(data, width, height, |x, y, _, _| {
+          let is_white = (((x + shift) / CHECKER_SIZE) + ((y + shift) / CHECKER_SIZE)) % 2 == 0;
+          if is_white {
+            [255, 255, 255]
+          } else {
+            [0, 0, 0]
+          }
+        })
+      }
+      TestPattern::Noise => {
+        let mut state = tick.wrapping_mul(2_654_435_761) | 1;
+        fill(data, width, height, |_, _, _, _| {
+          state ^= state << 13;
+          state ^= state >> 17;
+          state ^= state << 5;
+          let value = state as u8;
+          [value, value, value]
+        })
+      }
+      TestPattern::BouncingBall => {
+        fill(data, width, height, |_, _, _, _| [32, 32, 32]);
+        draw_ball(tick, frame)?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Fills a continuous BGR buffer with RGB colors returned by `color(x, y, width, height)`
+fn fill(
+  data: &mut [u8],
+  width: i32,
+  height: i32,
+  mut color: impl FnMut(i32, i32, i32, i32) -> [u8; 3],
+) {
+  for y in 0..height {
+    for x in 0..width {
+      let offset = ((y * width + x) * 3) as usize;
+      let [r, g, b] = color(x, y, width, height);
+
+      data[offset] = b;
+      data[offset + 1] = g;
+      data[offset + 2] = r;
+    }
+  }
+}
+
+/// Returns a SMPTE color bars color
+fn color_bars(x: i32, y: i32, width: i32, height: i32) -> [u8; 3] {
+  let bar = (x * 7 / width) as usize;
+
+  if y < height * 67 / 100 {
+    return SMPTE_BARS[bar];
+  }
+
+  if y < height * 75 / 100 {
+    return SMPTE_REVERSE_BARS[bar];
+  }
+
+  // Bottom row: -I, white, +Q, black (each 5/4 of a bar) then PLUGE and black
+  let position = x * 28 / width;
+  match position {
+    0..=4 => [0, 33, 76],
+    5..=9 => [255, 255, 255],
+    10..=14 => [50, 0, 106],
+    15..=19 => [19, 19, 19],
+    20 => [9, 9, 9],
+    21 => [19, 19, 19],
+    22 => [29, 29, 29],
+    _ => [19, 19, 19],
+  }
+}
+
+/// Returns a gradient ramps color (gray, red, green and blue horizontal ramps)
+fn gradient(x: i32, y: i32, width: i32, height: i32) -> [u8; 3] {
+  let value = (x * 255 / (width - 1)) as u8;
+
+  match y * 4 / height {
+    0 => [value, value, value],
+    1 => [value, 0, 0],
+    2 => [0, value, 0],
+    _ => [0, 0, value],
+  }
+}
+
+/// Returns a position bouncing between 0 and `max`
+fn bounce(position: i32, max: i32) -> i32 {
+  let position = position % (max * 2);
+
+  if position > max {
+    max * 2 - position
+  } else {
+    position
+  }
+}
+
+/// Draws a bouncing ball and a timestamp
+fn draw_ball(tick: u32, frame: &mut Mat) -> opencv::Result<()> {
+  let (width, height) = PATTERN_FRAME_SIZE;
+
+  let center = Point::new(
+    BALL_RADIUS + bounce(tick as i32 * 7, width - BALL_RADIUS * 2),
+    BALL_RADIUS + bounce(tick as i32 * 5, height - BALL_RADIUS * 2),
+  );
+
+  imgproc::circle(
+    frame,
+    center,
+    BALL_RADIUS,
+    Scalar::new(106.0, 143.0, 230.0, 0.0),
+    imgproc::FILLED,
+    imgproc::LINE_AA,
+    0,
+  )?;
+
+  let elapsed_ms = tick.wrapping_mul(PATTERN_TICK_MS);
+  let timestamp = format!(
+    "{:02}:{:02}.{:03}  #{}",
+    elapsed_ms / 60_000,
+    elapsed_ms / 1000 % 60,
+    elapsed_ms % 1000,
+    tick
+  );
+
+  imgproc::put_text(
+    frame,
+    &timestamp,
+    Point::new(16, height - 24),
+    imgproc::FONT_HERSHEY_SIMPLEX,
+    1.2,
+    Scalar::all(255.0),
+    2,
+    imgproc::LINE_AA,
+    false,
+  )
+}
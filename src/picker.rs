@@ -0,0 +1,141 @@
+use ratatui::{
+  style::{Color, Style, Stylize},
+  text::{Line, Span, Text},
+  widgets::{Block, BorderType, List, ListItem, ListState},
+};
+
+use crate::{handler::FrameHandlerConfig, pattern::TestPattern};
+
+/// Input offered by the camera picker
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum PickerEntry {
+  Camera(i32),
+  Pattern(TestPattern),
+}
+
+impl PickerEntry {
+  /// Makes the entry an active input of the frame handler
+  pub fn apply(&self, config: &mut FrameHandlerConfig) {
+    match self {
+      PickerEntry::Camera(id) => {
+        config.pattern = None;
+        config.camera.select(*id);
+      }
+      PickerEntry::Pattern(pattern) => config.pattern = Some(*pattern),
+    }
+  }
+}
+
+/// Camera picker popup.
+///
+/// Lists the device cameras (with their capabilities) followed by the test patterns.
+pub struct CameraPicker {
+  entries: Vec<(PickerEntry, Text<'static>)>,
+  state: ListState,
+}
+
+impl CameraPicker {
+  /// Creates a picker of the available inputs with the active one selected
+  pub fn new(config: &FrameHandlerConfig) -> Self {
+    let cameras = config.camera.devices.iter().map(|device| {
+      let mut lines = vec![Line::from(vec![
+        Span::from(format!("{}: ", device.id)),
+        Span::from(device.name.clone()).bold(),
+      ])];
+
+      let details = [device.bus_info.clone(), Some(device.summary())]
+        .into_iter()
+        .flatten()
+        .filter(|detail| !detail.is_empty())
+        .collect::<Vec<_>>();
+
+      if !details.is_empty() {
+        lines.push(Line::from(format!("   {}", details.join(" | "))).dim());
+      }
+
+      (PickerEntry::Camera(device.id), Text::from(lines))
+    });
+
+    let patterns = TestPattern::ALL.into_iter().map(|pattern| {
+      (
+        PickerEntry::Pattern(pattern),
+        Text::from(format!("Test pattern: {}", pattern.name())),
+      )
+    });
+
+    let entries = cameras.chain(patterns).collect::<Vec<_>>();
+
+    let active = match config.pattern {
+      Some(pattern) => PickerEntry::Pattern(pattern),
+      None => PickerEntry::Camera(config.camera.get_cam_id().copied().unwrap_or_default()),
+    };
+
+    let selected = entries
+      .iter()
+      .position(|(entry, _)| *entry == active)
+      .unwrap_or(0);
+
+    Self {
+      entries,
+      state: ListState::default().with_selected(Some(selected)),
+    }
+  }
+
+  /// Selects a previous entry (wraps around)
+  pub fn select_previous(&mut self) {
+    let selected = self.state.selected().unwrap_or(0);
+    let len = self.entries.len();
+
+    self.state.select(Some((selected + len - 1) % len));
+  }
+
+  /// Selects a next entry (wraps around)
+  pub fn select_next(&mut self) {
+    let selected = self.state.selected().unwrap_or(0);
+
+    self.state.select(Some((selected + 1) % self.entries.len()));
+  }
+
+  /// Returns a selected entry
+  pub fn selected(&self) -> PickerEntry {
+    self.entries[self.state.selected().unwrap_or(0)].0
+  }
+
+  /// Returns a popup size (width, height) including the border
+  pub fn size(&self) -> (u16, u16) {
+    let width = self
+      .entries
+      .iter()
+      .map(|(_, text)| text.width())
+      .max()
+      .unwrap_or(0);
+    let height = self
+      .entries
+      .iter()
+      .map(|(_, text)| text.height())
+      .sum::<usize>();
+
+    (width as u16 + 4, height as u16 + 2)
+  }
+
+  /// Returns a popup list widget (with the key hint) and its state
+  pub fn widget(&self, color: Color, hint: Line<'static>) -> (List<'static>, ListState) {
+    let items = self
+      .entries
+      .iter()
+      .map(|(_, text)| ListItem::new(text.clone()))
+      .collect::<Vec<_>>();
+
+    let list = List::new(items)
+      .block(
+        Block::bordered()
+          .border_type(BorderType::Rounded)
+          .border_style(Style::default().fg(color))
+          .title(" Pick a camera ")
+          .title_bottom(hint),
+      )
+      .highlight_style(Style::default().fg(color).reversed());
+
+    (list, self.state.clone())
+  }
+}
@@ -0,0 +1,180 @@
+use crate::font;
+
+/// Classic ASCII ramp (lightest glyph first)
+const CLASSIC_RAMP: &str = " .:-=+*#%@";
+
+/// Paul Bourke's 70 level ASCII ramp (lightest glyph first)
+const BOURKE_RAMP: &str =
+  " .'`^\",:;Il!i><~+_-?][}{1)(|\\/tfjrxnuvczXYUJCLQ0OZmwqpdbkhao*#MW&8%B@$";
+
+/// Block elements ramp (lightest glyph first)
+const BLOCKS_RAMP: &str = " ░▒▓█";
+
+/// Character ramp of the ASCII art mode
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum RampKind {
+  Classic,
+  Bourke,
+  Blocks,
+  Custom,
+}
+
+impl RampKind {
+  /// All ramps in the switch order
+  pub const ALL: [RampKind; 4] = [
+    RampKind::Classic,
+    RampKind::Bourke,
+    RampKind::Blocks,
+    RampKind::Custom,
+  ];
+
+  /// Returns a ramp name (shown in the camera title)
+  pub fn name(&self) -> &'static str {
+    match self {
+      RampKind::Classic => "classic",
+      RampKind::Bourke => "bourke",
+      RampKind::Blocks => "blocks",
+      RampKind::Custom => "custom",
+    }
+  }
+
+  /// Finds a ramp by the name
+  pub fn from_name(name: &str) -> Option<Self> {
+    let name = name.to_lowercase();
+    Self::ALL.into_iter().find(|kind| kind.name() == name)
+  }
+}
+
+/// ASCII art ramp with a calibrated intensity lookup.
+///
+/// Glyphs are ordered by the measured brightness of their bitmaps instead of the
+/// ramp order, so any custom string gives an even tone scale.
+pub struct AsciiRamp {
+  kind: RampKind,
+  custom: Option<String>,
+  is_inverted: bool,
+  lookup: [char; 256],
+}
+
+impl AsciiRamp {
+  /// Creates the classic ramp (a custom ramp is available if given)
+  pub fn new(custom: Option<String>) -> Self {
+    let custom = custom.filter(|custom| !custom.is_empty());
+
+    let mut ascii_ramp = Self {
+      kind: RampKind::Classic,
+      custom,
+      is_inverted: false,
+      lookup: [' '; 256],
+    };
+
+    ascii_ramp.calibrate();
+    ascii_ramp
+  }
+
+  pub fn kind(&self) -> RampKind {
+    self.kind
+  }
+
+  /// Returns glyphs of the custom ramp (if given)
+  pub fn custom(&self) -> Option<&str> {
+    self.custom.as_deref()
+  }
+
+  pub fn is_inverted(&self) -> bool {
+    self.is_inverted
+  }
+
+  /// Switches a ramp.
+  ///
+  /// Classic -> Bourke -> Blocks -> Custom (only if given) -> Classic
+  pub fn switch(&mut self) {
+    self.kind = match self.kind {
+      RampKind::Classic => RampKind::Bourke,
+      RampKind::Bourke => RampKind::Blocks,
+      RampKind::Blocks if self.custom.is_some() => RampKind::Custom,
+      RampKind::Blocks | RampKind::Custom => RampKind::Classic,
+    };
+
+    self.calibrate();
+  }
+
+  /// Sets a ramp (the custom one only if given)
+  pub fn set_kind(&mut self, kind: RampKind) {
+    if kind == RampKind::Custom && self.custom.is_none() {
+      return;
+    }
+
+    self.kind = kind;
+    self.calibrate();
+  }
+
+  /// Toggles the ramp inversion (dark glyphs on a light background)
+  pub fn toggle_invert(&mut self) {
+    self.is_inverted = !self.is_inverted;
+    self.calibrate();
+  }
+
+  /// Returns a glyph of the pixel intensity
+  pub fn glyph(&self, intensity: u8) -> char {
+    self.lookup[intensity as usize]
+  }
+
+  /// Returns glyphs of the active ramp
+  fn chars(&self) -> &str {
+    match self.kind {
+      RampKind::Classic => CLASSIC_RAMP,
+      RampKind::Bourke => BOURKE_RAMP,
+      RampKind::Blocks => BLOCKS_RAMP,
+      RampKind::Custom => self.custom.as_deref().unwrap_or(CLASSIC_RAMP),
+    }
+  }
+
+  /// Rebuilds the intensity lookup.
+  ///
+  /// Every intensity gets the glyph with the nearest normalized coverage.
+  fn calibrate(&mut self) {
+    let chars = self.chars().chars().collect::<Vec<_>>();
+    let last = (chars.len() - 1).max(1) as f32;
+
+    // Glyphs missing in the bitmap font keep their position in the ramp
+    let coverages = chars
+      .iter()
+      .enumerate()
+      .map(|(i, c)| glyph_coverage(*c).unwrap_or(i as f32 / last))
+      .collect::<Vec<_>>();
+
+    let min = coverages.iter().copied().fold(f32::MAX, f32::min);
+    let max = coverages.iter().copied().fold(f32::MIN, f32::max);
+    let range = (max - min).max(f32::EPSILON);
+
+    for (intensity, glyph) in self.lookup.iter_mut().enumerate() {
+      let mut level = intensity as f32 / 255.0;
+
+      if self.is_inverted {
+        level = 1.0 - level;
+      }
+
+      let nearest = coverages
+        .iter()
+        .map(|coverage| ((coverage - min) / range - level).abs())
+        .enumerate()
+        .min_by(|(_, lhs), (_, rhs)| lhs.total_cmp(rhs))
+        .map(|(i, _)| i)
+        .unwrap_or(0);
+
+      *glyph = chars[nearest];
+    }
+  }
+}
+
+/// Returns a fraction of the cell covered by the glyph (0.0 - 1.0)
+fn glyph_coverage(c: char) -> Option<f32> {
+  match c {
+    '░' => Some(0.25),
+    '▒' => Some(0.5),
+    '▓' => Some(0.75),
+    '█' => Some(1.0),
+    _ => font::coverage(c),
+  }
+}
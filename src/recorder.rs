@@ -0,0 +1,300 @@
+use std::{
+  path::{Path, PathBuf},
+  sync::{
+    atomic::{AtomicU64, Ordering},
+    mpsc::{Receiver, SyncSender},
+    Arc,
+  },
+  thread::JoinHandle,
+  time::Instant,
+};
+
+use opencv::{
+  core::{Mat, Size},
+  imgproc,
+  prelude::*,
+  videoio::VideoWriter,
+};
+
+#[cfg(not(feature = "opencv_old"))]
+use opencv::core::AlgorithmHint;
+
+use crate::channel::AppEvent;
+
+/// Maximal number of frames waiting for the writer (newer frames are dropped)
+const RECORD_QUEUE_SIZE: usize = 32;
+
+/// Video codec (and container) of recordings
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum VideoCodec {
+  Mp4v,
+  H264,
+  Mjpg,
+  Xvid,
+}
+
+impl VideoCodec {
+  /// All codecs (the first one is the default)
+  pub const ALL: [VideoCodec; 4] = [
+    VideoCodec::Mp4v,
+    VideoCodec::H264,
+    VideoCodec::Mjpg,
+    VideoCodec::Xvid,
+  ];
+
+  /// Returns a codec name (used by the CLI)
+  pub fn name(&self) -> &'static str {
+    match self {
+      VideoCodec::Mp4v => "mp4v",
+      VideoCodec::H264 => "h264",
+      VideoCodec::Mjpg => "mjpg",
+      VideoCodec::Xvid => "xvid",
+    }
+  }
+
+  /// Finds a codec by the name
+  pub fn from_name(name: &str) -> Option<Self> {
+    let name = name.to_lowercase();
+    Self::ALL.into_iter().find(|codec| codec.name() == name)
+  }
+
+  /// Returns a file extension of the codec container
+  pub fn extension(&self) -> &'static str {
+    match self {
+      VideoCodec::Mp4v | VideoCodec::H264 => "mp4",
+      VideoCodec::Mjpg | VideoCodec::Xvid => "avi",
+    }
+  }
+
+  /// Returns a FourCC code of the codec
+  fn fourcc(&self) -> opencv::Result<i32> {
+    match self {
+      VideoCodec::Mp4v => VideoWriter::fourcc('m', 'p', '4', 'v'),
+      VideoCodec::H264 => VideoWriter::fourcc('a', 'v', 'c', '1'),
+      VideoCodec::Mjpg => VideoWriter::fourcc('M', 'J', 'P', 'G'),
+      VideoCodec::Xvid => VideoWriter::fourcc('X', 'V', 'I', 'D'),
+    }
+  }
+}
+
+/// Video recording options
+#[derive(Clone, Copy, Debug)]
+pub struct RecordOptions {
+  /// Video codec (and container)
+  pub codec: VideoCodec,
+
+  /// Record frames processed by the camera mode instead of the raw frames
+  pub is_processed: bool,
+}
+
+impl Default for RecordOptions {
+  fn default() -> Self {
+    Self {
+      codec: VideoCodec::Mp4v,
+      is_processed: false,
+    }
+  }
+}
+
+/// Video recorder.
+///
+/// Frames are encoded on a separate thread, so the frame handler is never blocked.
+/// The recording ends when the recorder is dropped, the drop waits for the writer
+/// to finish the file.
+pub struct VideoRecorder {
+  tx: Option<SyncSender<(Mat, Instant)>>,
+  handle: Option<JoinHandle<()>>,
+
+  // Frames dropped while the writer lagged behind
+  dropped_count: Arc<AtomicU64>,
+}
+
+impl VideoRecorder {
+  /// Starts a writer thread (the file is created with the first frame).
+  ///
+  /// Reports the result as a toast when the recording ends.
+  pub fn start(
+    path: PathBuf,
+    codec: VideoCodec,
+    fps: f64,
+    app_tx: tokio::sync::mpsc::UnboundedSender<AppEvent>,
+  ) -> Self {
+    let (tx, rx) = std::sync::mpsc::sync_channel::<(Mat, Instant)>(RECORD_QUEUE_SIZE);
+    let dropped_count = Arc::new(AtomicU64::new(0));
+    let writer_dropped_count = dropped_count.clone();
+
+    let handle = std::thread::spawn(move || {
+      let message = match write_video(&path, codec, fps, rx) {
+        Ok(frame_count) => {
+          let dropped_count = writer_dropped_count.load(Ordering::Relaxed);
+          let dropped = if dropped_count > 0 {
+            format!(", {} dropped", dropped_count)
+          } else {
+            String::new()
+          };
+
+          format!(
+            "Video saved to {} ({} frames{})",
+            path.display(),
+            frame_count,
+            dropped
+          )
+        }
+        Err(err) => format!("Recording failed: {}", err.message),
+      };
+
+      let _ = app_tx.send(AppEvent::Toast(message));
+    });
+
+    Self {
+      tx: Some(tx),
+      handle: Some(handle),
+      dropped_count,
+    }
+  }
+
+  /// Queues a frame with its capture time.
+  ///
+  /// The frame is dropped if the writer lags behind, so the frame handler interval is kept.
+  pub fn push(&self, frame: &Mat) {
+    let Some(tx) = self.tx.as_ref() else {
+      return;
+    };
+
+    if tx.try_send((frame.clone(), Instant::now())).is_err() {
+      self.dropped_count.fetch_add(1, Ordering::Relaxed);
+    }
+  }
+
+  /// Checks if the writer thread has ended (an error occurred)
+  pub fn is_finished(&self) -> bool {
+    self
+      .handle
+      .as_ref()
+      .is_none_or(|handle| handle.is_finished())
+  }
+}
+
+impl Drop for VideoRecorder {
+  /// Closes the queue and waits until the writer releases the file
+  fn drop(&mut self) {
+    drop(self.tx.take());
+
+    if let Some(handle) = self.handle.take() {
+      let _ = handle.join();
+    }
+  }
+}
+
+/// Writes received frames into the video file, returns the number of written frames.
+///
+/// Frames are repeated (or skipped) by their capture times, so the video keeps the real
+/// speed even if the frame handler is late or frames are dropped.
+fn write_video(
+  path: &Path,
+  codec: VideoCodec,
+  fps: f64,
+  rx: Receiver<(Mat, Instant)>,
+) -> opencv::Result<u64> {
+  if let Some(dir) = path.parent() {
+    std::fs::create_dir_all(dir)
+      .map_err(|err| opencv::Error::new(opencv::core::StsError, err.to_string()))?;
+  }
+
+  let mut writer: Option<(VideoWriter, Size)> = None;
+  let mut frame_count = 0;
+  let mut started_at = None;
+
+  for (frame, captured_at) in rx {
+    let started_at = *started_at.get_or_insert(captured_at);
+    let position = (captured_at.duration_since(started_at).as_secs_f64() * fps).round() as u64 + 1;
+
+    if position <= frame_count {
+      continue;
+    }
+
+    let frame = into_bgr(frame)?;
+
+    if writer.is_none() {
+      let size = frame.size()?;
+      writer = Some((open_writer(path, codec, fps, size)?, size));
+    }
+
+    let Some((writer, size)) = writer.as_mut() else {
+      continue;
+    };
+
+    // The video size is fixed, frames of a resized camera window are scaled
+    let frame = if frame.size()? != *size {
+      let mut resized_frame = Mat::default();
+      imgproc::resize(
+        &frame,
+        &mut resized_frame,
+        *size,
+        0.0,
+        0.0,
+        imgproc::INTER_LINEAR,
+      )?;
+      resized_frame
+    } else {
+      frame
+    };
+
+    while frame_count < position {
+      writer.write(&frame)?;
+      frame_count += 1;
+    }
+  }
+
+  if let Some((mut writer, _)) = writer {
+    writer.release()?;
+  }
+
+  Ok(frame_count)
+}
+
+/// Opens a video writer of the frame size
+fn open_writer(
+  path: &Path,
+  codec: VideoCodec,
+  fps: f64,
+  size: Size,
+) -> opencv::Result<VideoWriter> {
+  let writer = VideoWriter::new(&path.to_string_lossy(), codec.fourcc()?, fps, size, true)?;
+
+  if !writer.is_opened()? {
+    return Err(opencv::Error::new(
+      opencv::core::StsError,
+      format!(
+        "Unable to write {} with the {} codec",
+        path.display(),
+        codec.name()
+      ),
+    ));
+  }
+
+  Ok(writer)
+}
+
+/// Converts a grayscale (processed) frame into BGR, the writer expects color frames
+fn into_bgr(frame: Mat) -> opencv::Result<Mat> {
+  if frame.channels() != 1 {
+    return Ok(frame);
+  }
+
+  let mut bgr_frame = Mat::default();
+
+  #[cfg(not(feature = "opencv_old"))]
+  imgproc::cvt_color(
+    &frame,
+    &mut bgr_frame,
+    imgproc::COLOR_GRAY2BGR,
+    0,
+    AlgorithmHint::ALGO_HINT_DEFAULT,
+  )?;
+
+  #[cfg(feature = "opencv_old")]
+  imgproc::cvt_color(&frame, &mut bgr_frame, imgproc::COLOR_GRAY2BGR, 0)?;
+
+  Ok(bgr_frame)
+}
@@ -0,0 +1,82 @@
+use std::path::{Path, PathBuf};
+
+use opencv::{
+  core::{Mat, Vector},
+  imgcodecs,
+  prelude::*,
+};
+
+/// JPEG quality of snapshots
+const SNAPSHOT_JPEG_QUALITY: i32 = 95;
+
+/// Snapshot image format
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum SnapshotFormat {
+  Png,
+  Jpeg,
+}
+
+impl SnapshotFormat {
+  /// Finds a format by the name (`png`, `jpg` or `jpeg`)
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name.to_lowercase().as_str() {
+      "png" => Some(SnapshotFormat::Png),
+      "jpg" | "jpeg" => Some(SnapshotFormat::Jpeg),
+      _ => None,
+    }
+  }
+
+  /// Returns a file extension of the format
+  pub fn extension(&self) -> &'static str {
+    match self {
+      SnapshotFormat::Png => "png",
+      SnapshotFormat::Jpeg => "jpg",
+    }
+  }
+}
+
+/// Returns a default snapshot directory (`~/Pictures/tuicam` or the working directory)
+pub fn default_dir() -> PathBuf {
+  dirs::picture_dir()
+    .map(|picture_dir| picture_dir.join("tuicam"))
+    .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// Returns a timestamped file path without an extension (`tuicam-2024-01-31_12-00-00-000`)
+pub fn timestamped_path(dir: &Path) -> PathBuf {
+  let timestamp = chrono::Local::now().format("%Y-%m-%d_%H-%M-%S-%3f");
+  dir.join(format!("tuicam-{}", timestamp))
+}
+
+/// Writes a full resolution BGR frame into the image file.
+///
+/// Blocks until the image is encoded, call it outside of the async runtime.
+pub fn save(frame: &Mat, path: &Path, format: SnapshotFormat) -> opencv::Result<()> {
+  if frame.empty() {
+    return Err(opencv::Error::new(
+      opencv::core::StsError,
+      "No frame captured yet",
+    ));
+  }
+
+  if let Some(dir) = path.parent() {
+    std::fs::create_dir_all(dir)
+      .map_err(|err| opencv::Error::new(opencv::core::StsError, err.to_string()))?;
+  }
+
+  let params = match format {
+    SnapshotFormat::Png => Vector::<i32>::new(),
+    SnapshotFormat::Jpeg => {
+      Vector::<i32>::from_slice(&[imgcodecs::IMWRITE_JPEG_QUALITY, SNAPSHOT_JPEG_QUALITY])
+    }
+  };
+
+  if !imgcodecs::imwrite(&path.to_string_lossy(), frame, &params)? {
+    return Err(opencv::Error::new(
+      opencv::core::StsError,
+      format!("Unable to write {}", path.display()),
+    ));
+  }
+
+  Ok(())
+}
@@ -0,0 +1,628 @@
+use std::{
+  collections::BTreeMap,
+  path::{Path, PathBuf},
+  time::Duration,
+};
+
+use opencv::{
+  core::Mat,
+  imgcodecs,
+  prelude::*,
+  videoio::{self, VideoCapture, VideoWriter},
+};
+
+use crate::{
+  device::CaptureMode,
+  handler::{CamWindowScale, FrameHandlerConfig, ImageConvertType},
+  pattern::TestPattern,
+};
+
+/// Default delay between two frames
+pub const DEFAULT_FRAME_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Still image file extensions (everything else is opened as a video file)
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "bmp", "webp", "tif", "tiff"];
+
+/// Source of BGR video frames
+pub trait FrameSource: Send {
+  /// Opens the source
+  fn open(&mut self) -> opencv::Result<()>;
+
+  /// Reads a next frame.
+  ///
+  /// Returns false if there is no new frame (the previous one stays valid).
+  fn read(&mut self, frame: &mut Mat) -> opencv::Result<bool>;
+
+  /// Returns a short human readable description
+  fn describe(&self) -> String;
+
+  /// Closes the source
+  fn close(&mut self) -> opencv::Result<()>;
+
+  /// Takes over the input settings of the frame handler config.
+  ///
+  /// Returns true if the source has to be reopened. The frame handler reopens it
+  /// outside of the config lock (opening a camera may take seconds).
+  fn needs_reopen(&mut self, _config: &FrameHandlerConfig) -> bool {
+    false
+  }
+
+  /// Synchronizes the source with the frame handler config.
+  ///
+  /// Called before every read.
+  fn sync(&mut self, _config: &mut FrameHandlerConfig) -> opencv::Result<()> {
+    Ok(())
+  }
+
+  /// Returns a delay between two frames
+  fn frame_interval(&self) -> Duration {
+    DEFAULT_FRAME_INTERVAL
+  }
+}
+
+impl<S: FrameSource + ?Sized> FrameSource for Box<S> {
+  fn open(&mut self) -> opencv::Result<()> {
+    (**self).open()
+  }
+
+  fn read(&mut self, frame: &mut Mat) -> opencv::Result<bool> {
+    (**self).read(frame)
+  }
+
+  fn describe(&self) -> String {
+    (**self).describe()
+  }
+
+  fn close(&mut self) -> opencv::Result<()> {
+    (**self).close()
+  }
+
+  fn needs_reopen(&mut self, config: &FrameHandlerConfig) -> bool {
+    (**self).needs_reopen(config)
+  }
+
+  fn sync(&mut self, config: &mut FrameHandlerConfig) -> opencv::Result<()> {
+    (**self).sync(config)
+  }
+
+  fn frame_interval(&self) -> Duration {
+    (**self).frame_interval()
+  }
+}
+
+/// Creates a frame source for the input path.
+///
+/// Still images are recognized by the file extension, other files are opened as a video.
+/// Without an input a device camera (or a test pattern) is used.
+pub fn from_input(input: Option<&Path>) -> Box<dyn FrameSource> {
+  match input {
+    Some(path) if is_image(path) => Box::new(ImageSource::new(path)),
+    Some(path) => Box::new(VideoFileSource::new(path)),
+    None => Box::new(LiveSource::new()),
+  }
+}
+
+/// Checks if the path is a still image
+pub fn is_image(path: &Path) -> bool {
+  path
+    .extension()
+    .and_then(|extension| extension.to_str())
+    .is_some_and(|extension| IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+}
+
+/// Creates an error of a source which is not opened
+fn not_opened_error(description: String) -> opencv::Error {
+  opencv::Error::new(
+    opencv::core::StsError,
+    format!("Unable to open {}", description),
+  )
+}
+
+/// Device camera source.
+///
+/// Follows the active camera (and its capture mode) of the frame handler config.
+pub struct DeviceSource {
+  cam_id: Option<i32>,
+  capture: Option<VideoCapture>,
+
+  /// Requested capture mode (reopens the camera when it changes)
+  requested_mode: Option<CaptureMode>,
+
+  /// Camera mode and scale of the automatic capture mode (a resize keeps the mode)
+  view: Option<(ImageConvertType, CamWindowScale)>,
+
+  /// Capture mode set on the open
+  capture_mode: Option<CaptureMode>,
+
+  /// Capture mode accepted by the camera (read back after the open)
+  negotiated_mode: Option<CaptureMode>,
+
+  /// Control values of the opened camera are read
+  are_controls_read: bool,
+}
+
+impl DeviceSource {
+  pub fn new() -> Self {
+    Self {
+      cam_id: None,
+      capture: None,
+      requested_mode: None,
+      view: None,
+      capture_mode: None,
+      negotiated_mode: None,
+      are_controls_read: false,
+    }
+  }
+}
+
+impl FrameSource for DeviceSource {
+  fn open(&mut self) -> opencv::Result<()> {
+    if let Some(cam_id) = self.cam_id {
+      let mut capture = VideoCapture::new(cam_id, videoio::CAP_ANY)?;
+
+      if !capture.is_opened()? {
+        return Err(not_opened_error(self.describe()));
+      }
+
+      // The camera picks the nearest supported mode, the pixel format goes first
+      if let Some(capture_mode) = self.capture_mode.as_ref() {
+        if let Some(code) = capture_mode.fourcc.as_deref().and_then(fourcc_code) {
+          capture.set(videoio::CAP_PROP_FOURCC, code? as f64)?;
+        }
+
+        if let Some((width, height)) = capture_mode.resolution {
+          capture.set(videoio::CAP_PROP_FRAME_WIDTH, width as f64)?;
+          capture.set(videoio::CAP_PROP_FRAME_HEIGHT, height as f64)?;
+        }
+
+        if let Some(fps) = capture_mode.fps {
+          capture.set(videoio::CAP_PROP_FPS, fps)?;
+        }
+      }
+
+      self.negotiated_mode = Some(negotiated_mode(&capture)?);
+      self.capture = Some(capture);
+      self.are_controls_read = false;
+    }
+
+    Ok(())
+  }
+
+  fn read(&mut self, frame: &mut Mat) -> opencv::Result<bool> {
+    match self.capture.as_mut() {
+      Some(capture) => capture.read(frame),
+      None => Err(not_opened_error(self.describe())),
+    }
+  }
+
+  fn describe(&self) -> String {
+    match (self.cam_id, self.negotiated_mode.as_ref()) {
+      (Some(cam_id), Some(negotiated_mode)) => format!("Camera {} ({})", cam_id, negotiated_mode),
+      (Some(cam_id), None) => format!("Camera {}", cam_id),
+      (None, _) => String::from("No camera"),
+    }
+  }
+
+  fn close(&mut self) -> opencv::Result<()> {
+    self.negotiated_mode = None;
+
+    if let Some(mut capture) = self.capture.take() {
+      capture.release()?;
+    }
+
+    Ok(())
+  }
+
+  /// Reopens the camera if the active camera or the requested capture mode changes.
+  ///
+  /// The automatic capture mode is renegotiated on a mode or scale switch
+  /// (if it picks another mode), a resize keeps it.
+  fn needs_reopen(&mut self, config: &FrameHandlerConfig) -> bool {
+    let cam_id = config.camera.get_cam_id().cloned();
+    let requested_mode = config.requested_capture_mode();
+    let view = Some((
+      config.image_convert_type.clone(),
+      config.cam_window_scale.clone(),
+    ));
+
+    let is_requested = cam_id != self.cam_id || requested_mode != self.requested_mode;
+
+    if !is_requested && view == self.view {
+      return false;
+    }
+
+    self.cam_id = cam_id;
+    self.requested_mode = requested_mode;
+    self.view = view;
+
+    let capture_mode = config.capture_mode();
+
+    if !is_requested && capture_mode == self.capture_mode {
+      return false;
+    }
+
+    self.capture_mode = capture_mode;
+
+    true
+  }
+
+  /// Applies requested control changes (and the camera preset after the open)
+  fn sync(&mut self, config: &mut FrameHandlerConfig) -> opencv::Result<()> {
+    let Some(capture) = self.capture.as_mut() else {
+      return Ok(());
+    };
+
+    if !self.are_controls_read {
+      config.camera_controls.clear();
+
+      if let Some(preset) = config.camera_preset().cloned() {
+        config.camera_controls.apply(&preset);
+      }
+    }
+
+    let no_ranges = BTreeMap::new();
+    let ranges = config
+      .camera
+      .active_device()
+      .map_or(&no_ranges, |device| &device.controls);
+
+    config
+      .camera_controls
+      .sync(capture, ranges, self.are_controls_read);
+    self.are_controls_read = true;
+
+    Ok(())
+  }
+}
+
+/// Returns a FourCC code of the pixel format (None if it's not 4 characters long)
+fn fourcc_code(fourcc: &str) -> Option<opencv::Result<i32>> {
+  let chars = fourcc.chars().collect::<Vec<_>>();
+
+  match chars[..] {
+    [c1, c2, c3, c4] => Some(VideoWriter::fourcc(c1, c2, c3, c4)),
+    _ => None,
+  }
+}
+
+/// Reads back the capture mode of an opened camera
+fn negotiated_mode(capture: &VideoCapture) -> opencv::Result<CaptureMode> {
+  let fourcc = capture.get(videoio::CAP_PROP_FOURCC)? as u32;
+  let width = capture.get(videoio::CAP_PROP_FRAME_WIDTH)? as u32;
+  let height = capture.get(videoio::CAP_PROP_FRAME_HEIGHT)? as u32;
+  let fps = capture.get(videoio::CAP_PROP_FPS)?;
+
+  // Backends without the property return 0 (or garbage)
+  let fourcc = fourcc
+    .to_le_bytes()
+    .iter()
+    .map(|byte| *byte as char)
+    .collect::<String>();
+  let is_printable = fourcc.chars().all(|c| c.is_ascii_graphic() || c == ' ');
+
+  Ok(CaptureMode {
+    fourcc: Some(fourcc.trim().to_string()).filter(|fourcc| is_printable && !fourcc.is_empty()),
+    resolution: (width > 0 && height > 0).then_some((width, height)),
+    fps: (fps > 0.0).then_some(fps),
+  })
+}
+
+/// Video file source.
+///
+/// Follows the playback state of the frame handler config.
+pub struct VideoFileSource {
+  path: PathBuf,
+  capture: Option<VideoCapture>,
+  fps: f64,
+  speed: f64,
+  is_paused: bool,
+  is_looping: bool,
+  is_ended: bool,
+  is_seeked: bool,
+
+  // Shown again at the end of the file (OpenCV empties the frame)
+  last_frame: Mat,
+}
+
+impl VideoFileSource {
+  pub fn new(path: &Path) -> Self {
+    Self {
+      path: path.to_path_buf(),
+      capture: None,
+      fps: 20.0,
+      speed: 1.0,
+      is_paused: false,
+      is_looping: true,
+      is_ended: false,
+      is_seeked: true,
+      last_frame: Mat::default(),
+    }
+  }
+}
+
+impl FrameSource for VideoFileSource {
+  fn open(&mut self) -> opencv::Result<()> {
+    let capture = VideoCapture::from_file(&self.path.to_string_lossy(), videoio::CAP_ANY)?;
+
+    if !capture.is_opened()? {
+      return Err(not_opened_error(self.describe()));
+    }
+
+    let fps = capture.get(videoio::CAP_PROP_FPS)?;
+    if fps > 0.0 {
+      self.fps = fps;
+    }
+
+    self.capture = Some(capture);
+
+    Ok(())
+  }
+
+  /// Keeps the last frame while paused and rewinds (or stops on the last frame)
+  /// at the end of the file.
+  fn read(&mut self, frame: &mut Mat) -> opencv::Result<bool> {
+    if self.is_paused && !self.is_seeked {
+      if frame.empty() {
+        *frame = self.last_frame.clone();
+      }
+
+      return Ok(false);
+    }
+
+    self.is_seeked = false;
+
+    let Some(capture) = self.capture.as_mut() else {
+      return Err(not_opened_error(self.describe()));
+    };
+
+    let mut is_read = capture.read(frame)?;
+
+    if !is_read && self.is_looping {
+      capture.set(videoio::CAP_PROP_POS_FRAMES, 0.0)?;
+      is_read = capture.read(frame)?;
+    }
+
+    if is_read {
+      self.last_frame = frame.clone();
+      return Ok(true);
+    }
+
+    self.is_ended = !self.is_looping;
+    *frame = self.last_frame.clone();
+
+    Ok(false)
+  }
+
+  fn describe(&self) -> String {
+    self
+      .path
+      .file_name()
+      .unwrap_or(self.path.as_os_str())
+      .to_string_lossy()
+      .to_string()
+  }
+
+  fn close(&mut self) -> opencv::Result<()> {
+    if let Some(mut capture) = self.capture.take() {
+      capture.release()?;
+    }
+
+    Ok(())
+  }
+
+  /// Applies a pending seek and takes over the playback state
+  fn sync(&mut self, config: &mut FrameHandlerConfig) -> opencv::Result<()> {
+    if std::mem::take(&mut self.is_ended) {
+      config.playback.is_paused = true;
+    }
+
+    let seek_offset_ms = config.playback.take_seek_offset();
+
+    if seek_offset_ms != 0.0 {
+      if let Some(capture) = self.capture.as_mut() {
+        let position_ms = capture.get(videoio::CAP_PROP_POS_MSEC)?;
+        capture.set(
+          videoio::CAP_PROP_POS_MSEC,
+          (position_ms + seek_offset_ms).max(0.0),
+        )?;
+        self.is_seeked = true;
+      }
+    }
+
+    self.is_paused = config.playback.is_paused;
+    self.is_looping = config.playback.is_looping;
+    self.speed = config.playback.speed();
+
+    Ok(())
+  }
+
+  fn frame_interval(&self) -> Duration {
+    Duration::from_secs_f64(1.0 / (self.fps * self.speed))
+  }
+}
+
+/// Still image source
+pub struct ImageSource {
+  path: PathBuf,
+  image: Option<Mat>,
+  is_read: bool,
+}
+
+impl ImageSource {
+  pub fn new(path: &Path) -> Self {
+    Self {
+      path: path.to_path_buf(),
+      image: None,
+      is_read: false,
+    }
+  }
+}
+
+impl FrameSource for ImageSource {
+  fn open(&mut self) -> opencv::Result<()> {
+    let image = imgcodecs::imread(&self.path.to_string_lossy(), imgcodecs::IMREAD_COLOR)?;
+
+    if image.empty() {
+      return Err(not_opened_error(self.describe()));
+    }
+
+    self.image = Some(image);
+    self.is_read = false;
+
+    Ok(())
+  }
+
+  /// Returns the image only once, it never changes.
+  fn read(&mut self, frame: &mut Mat) -> opencv::Result<bool> {
+    if self.is_read {
+      return Ok(false);
+    }
+
+    let Some(image) = self.image.as_ref() else {
+      return Err(not_opened_error(self.describe()));
+    };
+
+    image.copy_to(frame)?;
+    self.is_read = true;
+
+    Ok(true)
+  }
+
+  fn describe(&self) -> String {
+    self
+      .path
+      .file_name()
+      .unwrap_or(self.path.as_os_str())
+      .to_string_lossy()
+      .to_string()
+  }
+
+  fn close(&mut self) -> opencv::Result<()> {
+    self.image = None;
+    Ok(())
+  }
+}
+
+/// Synthetic frame generator.
+///
+/// Generates a test pattern, no hardware is needed.
+pub struct SyntheticSource {
+  pattern: TestPattern,
+  tick: u32,
+}
+
+impl SyntheticSource {
+  pub fn new(pattern: TestPattern) -> Self {
+    Self { pattern, tick: 0 }
+  }
+}
+
+impl FrameSource for SyntheticSource {
+  fn open(&mut self) -> opencv::Result<()> {
+    self.tick = 0;
+    Ok(())
+  }
+
+  fn read(&mut self, frame: &mut Mat) -> opencv::Result<bool> {
+    self.pattern.generate(self.tick, frame)?;
+    self.tick = self.tick.wrapping_add(1);
+
+    Ok(true)
+  }
+
+  fn describe(&self) -> String {
+    format!("Test pattern {}", self.pattern.name())
+  }
+
+  fn close(&mut self) -> opencv::Result<()> {
+    Ok(())
+  }
+
+  /// Follows the active test pattern of the frame handler config
+  fn sync(&mut self, config: &mut FrameHandlerConfig) -> opencv::Result<()> {
+    if let Some(pattern) = config.pattern {
+      if pattern != self.pattern {
+        self.pattern = pattern;
+        self.tick = 0;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Device camera or a test pattern.
+///
+/// Follows the active input of the frame handler config (switched by the `c` key).
+pub struct LiveSource {
+  device: DeviceSource,
+  synthetic: SyntheticSource,
+  is_synthetic: bool,
+  frame_interval: Duration,
+}
+
+impl LiveSource {
+  pub fn new() -> Self {
+    Self {
+      device: DeviceSource::new(),
+      synthetic: SyntheticSource::new(TestPattern::ColorBars),
+      is_synthetic: false,
+      frame_interval: DEFAULT_FRAME_INTERVAL,
+    }
+  }
+
+  /// Returns an active source
+  fn active(&mut self) -> &mut dyn FrameSource {
+    if self.is_synthetic {
+      &mut self.synthetic
+    } else {
+      &mut self.device
+    }
+  }
+}
+
+impl FrameSource for LiveSource {
+  fn open(&mut self) -> opencv::Result<()> {
+    self.active().open()
+  }
+
+  fn read(&mut self, frame: &mut Mat) -> opencv::Result<bool> {
+    self.active().read(frame)
+  }
+
+  fn describe(&self) -> String {
+    if self.is_synthetic {
+      self.synthetic.describe()
+    } else {
+      self.device.describe()
+    }
+  }
+
+  fn close(&mut self) -> opencv::Result<()> {
+    self.device.close()?;
+    self.synthetic.close()
+  }
+
+  /// Releases the device camera while a test pattern is active.
+  ///
+  /// The device follows the config under a test pattern as well (opened on the switch back).
+  fn needs_reopen(&mut self, config: &FrameHandlerConfig) -> bool {
+    let is_synthetic = config.pattern.is_some();
+    let is_switched = is_synthetic != self.is_synthetic;
+    self.is_synthetic = is_synthetic;
+
+    let is_device_changed = self.device.needs_reopen(config);
+
+    is_switched || (!is_synthetic && is_device_changed)
+  }
+
+  fn sync(&mut self, config: &mut FrameHandlerConfig) -> opencv::Result<()> {
+    self.frame_interval = config.frame_interval;
+    self.active().sync(config)
+  }
+
+  /// Follows the frame rate of the frame handler config
+  fn frame_interval(&self) -> Duration {
+    self.frame_interval
+  }
+}
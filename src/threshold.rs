@@ -0,0 +1,177 @@
+use opencv::{core::Mat, imgproc};
+
+/// Step of a fixed threshold value
+const VALUE_STEP: u8 = 8;
+
+/// Step of an adaptive block size (keeps the size odd)
+const BLOCK_SIZE_STEP: i32 = 2;
+
+/// Smallest adaptive block size in pixels
+pub const MIN_BLOCK_SIZE: i32 = 3;
+
+/// Largest adaptive block size in pixels
+pub const MAX_BLOCK_SIZE: i32 = 99;
+
+/// Constant subtracted from the adaptive local mean (keeps flat areas white)
+const ADAPTIVE_OFFSET: f64 = 5.0;
+
+/// Binarization method of the threshold modes
+#[derive(Eq, PartialEq, Clone, Copy, Debug)]
+pub enum ThresholdMethod {
+  Fixed,
+  Otsu,
+  AdaptiveMean,
+  AdaptiveGaussian,
+}
+
+impl ThresholdMethod {
+  /// All methods in the switch order
+  pub const ALL: [ThresholdMethod; 4] = [
+    ThresholdMethod::Fixed,
+    ThresholdMethod::Otsu,
+    ThresholdMethod::AdaptiveMean,
+    ThresholdMethod::AdaptiveGaussian,
+  ];
+
+  /// Returns a method name (used by the command line and the config file)
+  pub fn name(&self) -> &'static str {
+    match self {
+      ThresholdMethod::Fixed => "fixed",
+      ThresholdMethod::Otsu => "otsu",
+      ThresholdMethod::AdaptiveMean => "adaptive-mean",
+      ThresholdMethod::AdaptiveGaussian => "adaptive-gaussian",
+    }
+  }
+
+  /// Finds a method by the name
+  pub fn from_name(name: &str) -> Option<Self> {
+    let name = name.to_lowercase();
+    Self::ALL.into_iter().find(|method| method.name() == name)
+  }
+}
+
+/// Binary threshold of the threshold modes.
+///
+/// The fixed value is used as is, Otsu picks a global value of every frame
+/// and the adaptive methods compare a pixel with its block neighbourhood,
+/// which copes with uneven light.
+pub struct Threshold {
+  method: ThresholdMethod,
+  value: u8,
+  block_size: i32,
+}
+
+impl Threshold {
+  pub fn new(method: ThresholdMethod, value: u8, block_size: i32) -> Self {
+    Self {
+      method,
+      value,
+      block_size: odd_block_size(block_size),
+    }
+  }
+
+  pub fn method(&self) -> ThresholdMethod {
+    self.method
+  }
+
+  pub fn value(&self) -> u8 {
+    self.value
+  }
+
+  pub fn block_size(&self) -> i32 {
+    self.block_size
+  }
+
+  /// Switches a method.
+  ///
+  /// Fixed -> Otsu -> Adaptive mean -> Adaptive gaussian -> Fixed
+  pub fn switch(&mut self) {
+    let index = ThresholdMethod::ALL
+      .iter()
+      .position(|method| *method == self.method)
+      .unwrap_or(0);
+
+    self.method = ThresholdMethod::ALL[(index + 1) % ThresholdMethod::ALL.len()];
+  }
+
+  /// Raises (`1`) or lowers (`-1`) the fixed value or the adaptive block size.
+  ///
+  /// Otsu picks the value on its own, so it has nothing to adjust.
+  pub fn adjust(&mut self, direction: i32) {
+    match self.method {
+      ThresholdMethod::Fixed => {
+        let value = self.value as i32 + VALUE_STEP as i32 * direction;
+        self.value = value.clamp(0, u8::MAX as i32) as u8;
+      }
+      ThresholdMethod::Otsu => {}
+      ThresholdMethod::AdaptiveMean | ThresholdMethod::AdaptiveGaussian => {
+        self.block_size = odd_block_size(self.block_size + BLOCK_SIZE_STEP * direction);
+      }
+    }
+  }
+
+  /// Returns a description of the method with its parameter (shown in the camera title)
+  pub fn describe(&self) -> String {
+    match self.method {
+      ThresholdMethod::Fixed => format!("threshold {}", self.value),
+      ThresholdMethod::Otsu => String::from("otsu threshold"),
+      ThresholdMethod::AdaptiveMean | ThresholdMethod::AdaptiveGaussian => {
+        format!("{} threshold {}px", self.method.name(), self.block_size)
+      }
+    }
+  }
+
+  /// Converts a grayscale frame into a binary one (0 or 255)
+  pub fn apply(&self, gray_frame: &Mat, binary_frame: &mut Mat) -> opencv::Result<()> {
+    match self.method {
+      ThresholdMethod::Fixed => {
+        imgproc::threshold(
+          gray_frame,
+          binary_frame,
+          self.value as f64,
+          255.0,
+          imgproc::THRESH_BINARY,
+        )?;
+      }
+      ThresholdMethod::Otsu => {
+        imgproc::threshold(
+          gray_frame,
+          binary_frame,
+          0.0,
+          255.0,
+          imgproc::THRESH_BINARY | imgproc::THRESH_OTSU,
+        )?;
+      }
+      ThresholdMethod::AdaptiveMean | ThresholdMethod::AdaptiveGaussian => {
+        let adaptive_method = if self.method == ThresholdMethod::AdaptiveMean {
+          imgproc::ADAPTIVE_THRESH_MEAN_C
+        } else {
+          imgproc::ADAPTIVE_THRESH_GAUSSIAN_C
+        };
+
+        imgproc::adaptive_threshold(
+          gray_frame,
+          binary_frame,
+          255.0,
+          adaptive_method,
+          imgproc::THRESH_BINARY,
+          self.block_size,
+          ADAPTIVE_OFFSET,
+        )?;
+      }
+    }
+
+    Ok(())
+  }
+}
+
+/// Returns an odd block size in the allowed range (OpenCV rejects even sizes)
+fn odd_block_size(block_size: i32) -> i32 {
+  let block_size = block_size.clamp(MIN_BLOCK_SIZE, MAX_BLOCK_SIZE);
+
+  if block_size % 2 == 0 {
+    block_size + 1
+  } else {
+    block_size
+  }
+}